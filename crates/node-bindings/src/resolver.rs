@@ -18,6 +18,12 @@ use napi::JsUnknown;
 use napi::Ref;
 use napi::Result;
 use napi_derive::napi;
+#[cfg(not(target_arch = "wasm32"))]
+use parcel_filesystem::watch::WatchCallback;
+#[cfg(not(target_arch = "wasm32"))]
+use parcel_filesystem::watch::WatchHandle;
+#[cfg(not(target_arch = "wasm32"))]
+use parcel_filesystem::FileType;
 use parcel_resolver::ExportsCondition;
 use parcel_resolver::Extensions;
 use parcel_resolver::Fields;
@@ -153,6 +159,20 @@ enum EitherFs<A, B> {
 
 #[cfg(not(target_arch = "wasm32"))]
 impl<A: FileSystem, B: FileSystem> FileSystem for EitherFs<A, B> {
+  fn cwd(&self) -> std::io::Result<PathBuf> {
+    match self {
+      EitherFs::A(a) => a.cwd(),
+      EitherFs::B(b) => b.cwd(),
+    }
+  }
+
+  fn canonicalize_base<P: AsRef<Path>>(&self, path: P) -> std::io::Result<PathBuf> {
+    match self {
+      EitherFs::A(a) => a.canonicalize_base(path),
+      EitherFs::B(b) => b.canonicalize_base(path),
+    }
+  }
+
   fn canonicalize<P: AsRef<Path>>(
     &self,
     path: P,
@@ -164,6 +184,13 @@ impl<A: FileSystem, B: FileSystem> FileSystem for EitherFs<A, B> {
     }
   }
 
+  fn read<P: AsRef<Path>>(&self, path: P) -> std::io::Result<Vec<u8>> {
+    match self {
+      EitherFs::A(a) => a.read(path),
+      EitherFs::B(b) => b.read(path),
+    }
+  }
+
   fn read_to_string<P: AsRef<Path>>(&self, path: P) -> std::io::Result<String> {
     match self {
       EitherFs::A(a) => a.read_to_string(path),
@@ -171,6 +198,20 @@ impl<A: FileSystem, B: FileSystem> FileSystem for EitherFs<A, B> {
     }
   }
 
+  fn write<P: AsRef<Path>>(&self, path: P, contents: &[u8]) -> std::io::Result<()> {
+    match self {
+      EitherFs::A(a) => a.write(path, contents),
+      EitherFs::B(b) => b.write(path, contents),
+    }
+  }
+
+  fn create_dir_all<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+    match self {
+      EitherFs::A(a) => a.create_dir_all(path),
+      EitherFs::B(b) => b.create_dir_all(path),
+    }
+  }
+
   fn is_file<P: AsRef<Path>>(&self, path: P) -> bool {
     match self {
       EitherFs::A(a) => a.is_file(path),
@@ -184,6 +225,27 @@ impl<A: FileSystem, B: FileSystem> FileSystem for EitherFs<A, B> {
       EitherFs::B(b) => b.is_dir(path),
     }
   }
+
+  fn file_type<P: AsRef<Path>>(&self, path: P) -> Option<FileType> {
+    match self {
+      EitherFs::A(a) => a.file_type(path),
+      EitherFs::B(b) => b.file_type(path),
+    }
+  }
+
+  fn read_dir<P: AsRef<Path>>(&self, path: P) -> std::io::Result<Vec<PathBuf>> {
+    match self {
+      EitherFs::A(a) => a.read_dir(path),
+      EitherFs::B(b) => b.read_dir(path),
+    }
+  }
+
+  fn watch(&self, paths: &[PathBuf], callback: WatchCallback) -> std::io::Result<WatchHandle> {
+    match self {
+      EitherFs::A(a) => a.watch(paths, callback),
+      EitherFs::B(b) => b.watch(paths, callback),
+    }
+  }
 }
 
 #[napi(object)]
@@ -254,7 +316,7 @@ impl Resolver {
         is_dir: FunctionRef::new(env, fs.is_dir)?,
       })
     } else {
-      EitherFs::B(OsFileSystem)
+      EitherFs::B(OsFileSystem::default())
     };
     #[cfg(target_arch = "wasm32")]
     let fs = {
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+
+/// Describes which plugins changed between two ParcelConfigs
+///
+/// Plugin lists are reported as a changed flag, while pattern maps report the patterns that were
+/// added, removed, or had their plugins changed.
+///
+#[derive(Debug, Default, PartialEq)]
+pub struct ConfigDiff {
+  pub bundler: bool,
+  pub compressors: Vec<String>,
+  pub namers: bool,
+  pub optimizers: Vec<String>,
+  pub packagers: Vec<String>,
+  pub reporters: bool,
+  pub resolvers: bool,
+  pub runtimes: bool,
+  pub transformers: Vec<String>,
+  pub validators: Vec<String>,
+}
+
+impl ConfigDiff {
+  /// Whether the configs are equivalent
+  pub fn is_empty(&self) -> bool {
+    !self.reporters && !self.requires_rebuild()
+  }
+
+  /// Whether the change affects build output
+  ///
+  /// Reporters do not affect the output of a build, so changing them alone does not require work
+  /// to be invalidated.
+  ///
+  pub fn requires_rebuild(&self) -> bool {
+    self.bundler
+      || !self.compressors.is_empty()
+      || self.namers
+      || !self.optimizers.is_empty()
+      || !self.packagers.is_empty()
+      || self.resolvers
+      || self.runtimes
+      || !self.transformers.is_empty()
+      || !self.validators.is_empty()
+  }
+}
+
+/// Returns the patterns that were added, removed, or changed between two maps
+///
+/// Patterns are compared by the key they map to, so that patterns written differently but
+/// matching the same paths are not reported as changed.
+pub(crate) fn changed_patterns<T: PartialEq>(
+  map: &IndexMap<String, T>,
+  other_map: &IndexMap<String, T>,
+  key: fn(&str) -> String,
+) -> Vec<String> {
+  let keys: HashSet<String> = map.keys().map(|pattern| key(pattern)).collect();
+  let other_values: HashMap<String, &T> = other_map
+    .iter()
+    .map(|(pattern, value)| (key(pattern), value))
+    .collect();
+
+  let changed = map
+    .iter()
+    .filter(|(pattern, value)| other_values.get(&key(pattern)) != Some(value))
+    .map(|(pattern, _)| pattern);
+
+  let added = other_map
+    .keys()
+    .filter(|pattern| !keys.contains(&key(pattern)));
+
+  changed.chain(added).cloned().collect()
+}
@@ -2,10 +2,51 @@ use std::path::PathBuf;
 
 use thiserror::Error;
 
+use crate::source_location::SourceLocation;
+
 #[derive(Debug, Error)]
 pub enum ConfigError {
+  #[error("Empty extends at index {index} in {path}")]
+  EmptyExtends { index: usize, path: PathBuf },
+  #[error("The {phase} pipeline for {pattern} is empty")]
+  EmptyPipeline { phase: String, pattern: String },
+  #[error(
+    "Extends are nested deeper than the maximum depth of {max_depth}: {}",
+    .chain.iter().map(|p| p.display().to_string()).collect::<Vec<String>>().join(" -> ")
+  )]
+  ExtendsTooDeep {
+    chain: Vec<PathBuf>,
+    max_depth: usize,
+  },
   #[error("{0}")]
   InvalidConfig(String),
+  #[error(
+    "Invalid plugin entry true at index {index} of {phase}{} in {}: plugins can only be disabled with false",
+    .pattern.as_ref().map_or_else(String::new, |pattern| format!(" for {pattern}")),
+    .path.display()
+  )]
+  InvalidPluginEntry {
+    index: usize,
+    path: PathBuf,
+    pattern: Option<String>,
+    phase: String,
+  },
+  #[error(
+    "Invalid plugin name {name} at {}: {message}",
+    .location.as_ref().map_or_else(|| .path.display().to_string(), ToString::to_string)
+  )]
+  InvalidPluginName {
+    location: Option<SourceLocation>,
+    message: String,
+    name: String,
+    path: PathBuf,
+  },
+  #[error("Failed to load {path}")]
+  LoadFailure {
+    path: PathBuf,
+    #[source]
+    source: Box<dyn std::error::Error>,
+  },
   #[error("No {phase} found for {path} with pipeline {pipeline:?}")]
   MissingPlugin {
     path: PathBuf,
@@ -26,6 +67,10 @@ pub enum ConfigError {
     #[source]
     source: std::io::Error,
   },
+  #[error("Undefined variable {name} in {path}")]
+  UndefinedVariable { name: String, path: PathBuf },
+  #[error("No resolver could resolve {specifier} from {from}")]
+  UnhandledSpecifier { from: PathBuf, specifier: String },
   #[error("Failed to resolve {config_type} {specifier} from {from}")]
   UnresolvedConfig {
     config_type: String,
@@ -34,4 +79,54 @@ pub enum ConfigError {
     #[source]
     source: Box<dyn std::error::Error>,
   },
+  #[error(
+    "Failed to resolve extended config {specifier} from {from}, tried {}",
+    .candidates.join(", ")
+  )]
+  UnresolvedExtends {
+    candidates: Vec<String>,
+    from: PathBuf,
+    specifier: String,
+    #[source]
+    source: Box<dyn std::error::Error>,
+  },
+  #[error("Failed to resolve {specifier} from {from} with {resolver}")]
+  UnresolvedSpecifier {
+    from: PathBuf,
+    resolver: String,
+    specifier: String,
+    #[source]
+    source: Box<dyn std::error::Error>,
+  },
+}
+
+/// Problems with a config that do not prevent it from being used
+#[derive(Clone, Debug, Error, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum ConfigWarning {
+  #[error("{specifier} at index {index} of \"extends\" in {} is a duplicate and is ignored", .path.display())]
+  DuplicateExtends {
+    index: usize,
+    path: PathBuf,
+    specifier: String,
+  },
+  #[error(
+    "The plugin \"{package_name}\" is not compatible with the current version of Parcel. Requires \"{range}\" but the current version is \"{version}\""
+  )]
+  IncompatiblePlugin {
+    package_name: String,
+    range: String,
+    version: String,
+  },
+  #[error("The {phase} pipelines {pipelines:?} are not produced by any transformers")]
+  OrphanPipelines {
+    phase: String,
+    pipelines: Vec<String>,
+  },
+  #[error("The {pipeline} pipeline is reserved and cannot be used in {phase}")]
+  ReservedPipeline { phase: String, pipeline: String },
+  #[error(
+    "The {phase} pipeline for {pattern} only inherits with \"...\", but nothing was inherited"
+  )]
+  UnsatisfiedSpread { phase: String, pattern: String },
 }
@@ -0,0 +1,196 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+#[cfg(test)]
+use mockall::automock;
+use parcel_filesystem::FileSystem;
+
+/// Whether an "extends" specifier refers to a config hosted at a URL
+pub fn is_url(specifier: &str) -> bool {
+  specifier.starts_with("https://") || specifier.starts_with("http://")
+}
+
+/// Fetches the contents of configs that are extended by URL
+///
+/// This allows embedders to choose the HTTP client, and tests to run without network access.
+#[cfg_attr(test, automock)]
+pub trait ConfigFetcher {
+  fn fetch(&self, url: &str) -> Result<String, Box<dyn std::error::Error>>;
+}
+
+/// Caches fetched configs on disk, so that each URL is only fetched once
+///
+/// The cache directory is created when the first config is cached, and a config that cannot be
+/// cached is returned as an error rather than being fetched again on every load.
+///
+pub struct CachedConfigFetcher<'a, F, T> {
+  cache_dir: PathBuf,
+  fetcher: F,
+  fs: &'a T,
+}
+
+impl<'a, F: ConfigFetcher, T: FileSystem> CachedConfigFetcher<'a, F, T> {
+  pub fn new(fetcher: F, fs: &'a T, cache_dir: &Path) -> Self {
+    CachedConfigFetcher {
+      cache_dir: PathBuf::from(cache_dir),
+      fetcher,
+      fs,
+    }
+  }
+
+  /// Returns the file a URL is cached in, named by a hash of the URL that is stable across runs
+  fn cache_path(&self, url: &str) -> PathBuf {
+    // FNV-1a
+    let hash = url.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+      (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+
+    self.cache_dir.join(format!("{:016x}.json", hash))
+  }
+}
+
+impl<'a, F: ConfigFetcher, T: FileSystem> ConfigFetcher for CachedConfigFetcher<'a, F, T> {
+  fn fetch(&self, url: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let cache_path = self.cache_path(url);
+    if let Ok(contents) = self.fs.read_to_string(&cache_path) {
+      return Ok(contents);
+    }
+
+    let contents = self.fetcher.fetch(url)?;
+
+    self.fs.create_dir_all(&self.cache_dir)?;
+    self.fs.write(&cache_path, contents.as_bytes())?;
+
+    Ok(contents)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::cell::RefCell;
+
+  use mockall::predicate::eq;
+  use parcel_filesystem::in_memory_file_system::InMemoryFileSystem;
+
+  use super::*;
+
+  /// Allows writes through the file system trait, which the in memory file system does not support
+  #[derive(Default)]
+  struct WritableFileSystem(RefCell<InMemoryFileSystem>);
+
+  impl FileSystem for WritableFileSystem {
+    fn read_to_string<P: AsRef<Path>>(&self, path: P) -> std::io::Result<String> {
+      self.0.borrow().read_to_string(path)
+    }
+
+    fn write<P: AsRef<Path>>(&self, path: P, contents: &[u8]) -> std::io::Result<()> {
+      if !self.is_dir(path.as_ref().parent().unwrap()) {
+        return Err(std::io::Error::new(
+          std::io::ErrorKind::NotFound,
+          "Directory not found",
+        ));
+      }
+
+      self.0.borrow_mut().write_file(path, contents);
+      Ok(())
+    }
+
+    fn create_dir_all<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+      for dir in path.as_ref().ancestors() {
+        self.0.borrow_mut().create_directory(dir);
+      }
+
+      Ok(())
+    }
+
+    fn is_file<P: AsRef<Path>>(&self, path: P) -> bool {
+      self.0.borrow().is_file(path)
+    }
+
+    fn is_dir<P: AsRef<Path>>(&self, path: P) -> bool {
+      self.0.borrow().is_dir(path)
+    }
+  }
+
+  mod is_url {
+    use super::*;
+
+    #[test]
+    fn returns_true_for_http_urls() {
+      assert!(is_url("https://example.com/.parcelrc"));
+      assert!(is_url("http://example.com/.parcelrc"));
+    }
+
+    #[test]
+    fn returns_false_for_other_specifiers() {
+      assert!(!is_url("@parcel/config-default"));
+      assert!(!is_url("./.parcelrc"));
+      assert!(!is_url("file:///.parcelrc"));
+    }
+  }
+
+  mod fetch {
+    use super::*;
+
+    #[test]
+    fn fetches_each_url_once() {
+      let url = "https://example.com/.parcelrc";
+      let mut fetcher = MockConfigFetcher::new();
+      fetcher
+        .expect_fetch()
+        .with(eq(url))
+        .times(1)
+        .returning(|_| Ok(String::from("{}")));
+
+      let fs = WritableFileSystem::default();
+      let cache_dir = Path::new("/cache");
+      let cached_fetcher = CachedConfigFetcher::new(fetcher, &fs, cache_dir);
+
+      for _ in 0..2 {
+        assert_eq!(
+          cached_fetcher.fetch(url).map_err(|e| e.to_string()),
+          Ok(String::from("{}"))
+        );
+      }
+
+      assert!(fs.is_file(cached_fetcher.cache_path(url)));
+    }
+
+    #[test]
+    fn returns_errors_from_caching() {
+      let mut fetcher = MockConfigFetcher::new();
+      fetcher.expect_fetch().returning(|_| Ok(String::from("{}")));
+
+      let fs = InMemoryFileSystem::default();
+      let cached_fetcher = CachedConfigFetcher::new(fetcher, &fs, Path::new("/cache"));
+
+      assert_eq!(
+        cached_fetcher
+          .fetch("https://example.com/.parcelrc")
+          .map_err(|e| e.to_string()),
+        Err(String::from("Creating directories is not supported"))
+      );
+    }
+
+    #[test]
+    fn does_not_cache_errors() {
+      let mut fetcher = MockConfigFetcher::new();
+      fetcher
+        .expect_fetch()
+        .times(2)
+        .returning(|_| Err("Offline".into()));
+
+      let fs = WritableFileSystem::default();
+      let cached_fetcher = CachedConfigFetcher::new(fetcher, &fs, Path::new("/cache"));
+
+      for _ in 0..2 {
+        assert_eq!(
+          cached_fetcher
+            .fetch("https://example.com/.parcelrc")
+            .map_err(|e| e.to_string()),
+          Err(String::from("Offline"))
+        );
+      }
+    }
+  }
+}
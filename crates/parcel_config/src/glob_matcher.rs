@@ -0,0 +1,359 @@
+use glob_match::glob_match;
+#[cfg(feature = "fast-glob")]
+use regex::Regex;
+
+/// Matches paths against a glob from .parcelrc
+///
+/// When the `fast-glob` feature is enabled, globs are compiled into a regex up front so that
+/// repeated lookups do not need to reparse the glob. Globs using syntax that cannot be translated
+/// with identical semantics fall back to `glob_match`.
+///
+/// Globs without any glob syntax, such as `package.json`, are compared as plain strings.
+#[derive(Debug)]
+pub(crate) struct GlobMatcher {
+  glob: String,
+  is_literal: bool,
+  #[cfg(feature = "fast-glob")]
+  compiled: Option<(Regex, bool)>,
+}
+
+impl GlobMatcher {
+  pub fn new(glob: &str) -> Self {
+    Self {
+      glob: String::from(glob),
+      is_literal: !glob.contains(['*', '?', '{', '}', '[', ']', '!', '\\']),
+      #[cfg(feature = "fast-glob")]
+      compiled: glob_to_regex(glob)
+        .and_then(|(regex, negated)| Regex::new(&regex).ok().map(|regex| (regex, negated))),
+    }
+  }
+
+  pub fn is_match(&self, path: &str) -> bool {
+    // glob_match treats a trailing separator specially, so defer to it in that case
+    if path.ends_with('/') {
+      return glob_match(&self.glob, path);
+    }
+
+    if self.is_literal {
+      return self.glob == path;
+    }
+
+    #[cfg(feature = "fast-glob")]
+    if let Some((regex, negated)) = &self.compiled {
+      return regex.is_match(path) != *negated;
+    }
+
+    glob_match(&self.glob, path)
+  }
+}
+
+impl PartialEq for GlobMatcher {
+  fn eq(&self, other: &Self) -> bool {
+    self.glob == other.glob
+  }
+}
+
+/// Sorts and dedups the alternatives of brace sets, so that globs matching the same paths compare
+/// equal
+///
+/// Braces are only reordered when doing so cannot change what the glob matches, which requires
+/// literal alternatives. glob_match does not backtrack into braces, so alternatives that are a
+/// prefix of another are only reordered when nothing follows the braces.
+pub(crate) fn normalize_glob(glob: &str) -> String {
+  if glob.contains('\\') {
+    return String::from(glob);
+  }
+
+  let mut normalized = String::new();
+  let mut rest = glob;
+  while let Some(start) = rest.find('{') {
+    let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+      break;
+    };
+
+    normalized.push_str(&rest[..=start]);
+
+    let mut alternatives: Vec<&str> = rest[start + 1..end].split(',').collect();
+    let is_literal = alternatives
+      .iter()
+      .all(|a| !a.is_empty() && !a.contains(['*', '?', '[', '{']));
+
+    let has_prefixes = alternatives.iter().enumerate().any(|(i, a)| {
+      alternatives
+        .iter()
+        .enumerate()
+        .any(|(j, b)| i != j && b.starts_with(a))
+    });
+
+    if is_literal && (!has_prefixes || end + 1 == rest.len()) {
+      alternatives.sort();
+      alternatives.dedup();
+    }
+
+    normalized.push_str(&alternatives.join(","));
+    normalized.push('}');
+    rest = &rest[end + 1..];
+  }
+
+  normalized.push_str(rest);
+  normalized
+}
+
+/// Translates a glob into an anchored regex, returning whether the glob was negated
+///
+/// Returns None for any syntax where the translation would not match `glob_match` exactly, such as
+/// escapes, negated character classes, and nested or non-literal braces.
+#[cfg(feature = "fast-glob")]
+fn glob_to_regex(glob: &str) -> Option<(String, bool)> {
+  let negations = glob.chars().take_while(|c| *c == '!').count();
+  let glob: Vec<char> = glob.chars().skip(negations).collect();
+  let mut regex = String::from("^");
+  let mut index = 0;
+
+  while index < glob.len() {
+    match glob[index] {
+      '*' if glob.get(index + 1) == Some(&'*') => {
+        // Globstars are only supported as whole segments, and glob_match does not treat a leading
+        // globstar as a segment when the glob is negated
+        if (index > 0 && glob[index - 1] != '/') || (index == 0 && negations > 0) {
+          return None;
+        }
+
+        match glob.get(index + 2) {
+          None => regex.push_str(".*"),
+          Some('/') if index + 3 < glob.len() => regex.push_str("(?:.*/)?"),
+          Some(_) => return None,
+        }
+
+        index += 3;
+      }
+      '*' => {
+        regex.push_str("[^/]*");
+        index += 1;
+      }
+      '?' => {
+        regex.push_str("[^/]");
+        index += 1;
+      }
+      '[' => {
+        // A closing bracket directly after the opening one is part of the class
+        let end = glob.get(index + 2..)?.iter().position(|c| *c == ']')? + index + 2;
+        let class = &glob[index + 1..end];
+        if matches!(class[0], '!' | '^') || class.iter().any(|c| matches!(c, '/' | '\\' | '[')) {
+          return None;
+        }
+
+        regex.push('[');
+        for c in class {
+          if *c == '-' {
+            regex.push('-');
+          } else {
+            regex.push_str(&regex::escape(&c.to_string()));
+          }
+        }
+        regex.push(']');
+        index = end + 1;
+      }
+      '{' => {
+        let end = glob[index..].iter().position(|c| *c == '}')? + index;
+        let alternatives: Vec<String> = glob[index + 1..end]
+          .split(|c| *c == ',')
+          .map(|alternative| alternative.iter().collect())
+          .collect();
+
+        if alternatives
+          .iter()
+          .any(|a| a.is_empty() || a.contains(['*', '?', '[', '{', '\\']))
+        {
+          return None;
+        }
+
+        // glob_match takes the first alternative that matches without backtracking, which only
+        // agrees with a regex alternation when no alternative is a prefix of another, or when
+        // nothing follows the braces
+        let has_prefixes = alternatives.iter().enumerate().any(|(i, a)| {
+          alternatives
+            .iter()
+            .enumerate()
+            .any(|(j, b)| i != j && b.starts_with(a.as_str()))
+        });
+
+        if has_prefixes && end + 1 != glob.len() {
+          return None;
+        }
+
+        regex.push_str("(?:");
+        regex.push_str(
+          &alternatives
+            .iter()
+            .map(|a| regex::escape(a))
+            .collect::<Vec<String>>()
+            .join("|"),
+        );
+        regex.push(')');
+        index = end + 1;
+      }
+      '}' | ',' | '\\' | ']' => return None,
+      c => {
+        regex.push_str(&regex::escape(&c.to_string()));
+        index += 1;
+      }
+    }
+  }
+
+  regex.push('$');
+
+  Some((regex, negations % 2 == 1))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_globs() {
+    let matcher = GlobMatcher::new("*.{js,mjs,jsm,jsx,es6,cjs,ts,tsx}");
+
+    assert!(matcher.is_match("a.js"));
+    assert!(matcher.is_match("a.tsx"));
+    assert!(!matcher.is_match("a.css"));
+    assert!(!matcher.is_match("src/a.js"));
+
+    let matcher = GlobMatcher::new("**/node_modules/**");
+
+    assert!(matcher.is_match("node_modules/a.js"));
+    assert!(matcher.is_match("src/node_modules/a/b.js"));
+    assert!(!matcher.is_match("src/a.js"));
+  }
+
+  #[test]
+  fn matches_literal_globs() {
+    let globs = ["package.json", "src/a.js", "a", ".parcelrc", "a/"];
+    let paths = [
+      "package.json",
+      "src/package.json",
+      "src/a.js",
+      "a",
+      "a/",
+      "a/b",
+      "b",
+      ".parcelrc",
+      "",
+    ];
+
+    for glob in globs {
+      let matcher = GlobMatcher::new(glob);
+      assert!(matcher.is_literal, "{glob} should be literal");
+
+      for path in paths {
+        assert_eq!(
+          matcher.is_match(path),
+          glob_match(glob, path),
+          "{glob} matching {path}"
+        );
+      }
+    }
+
+    assert!(!GlobMatcher::new("*.json").is_literal);
+    assert!(!GlobMatcher::new("!package.json").is_literal);
+  }
+
+  #[test]
+  fn normalizes_brace_alternatives() {
+    assert_eq!(normalize_glob("*.{ts,js}"), "*.{js,ts}");
+    assert_eq!(normalize_glob("*.{js,ts,js}"), "*.{js,ts}");
+    assert_eq!(normalize_glob("{b,a}/*.{ts,js}"), "{a,b}/*.{js,ts}");
+    assert_eq!(normalize_glob("*.{jsx,js}"), "*.{js,jsx}");
+  }
+
+  #[test]
+  fn keeps_braces_that_would_match_differently() {
+    assert_eq!(normalize_glob("*.{jsx,js}.map"), "*.{jsx,js}.map");
+    assert_eq!(normalize_glob("*.{ts,*.js}"), "*.{ts,*.js}");
+    assert_eq!(normalize_glob("*.{ts,{js,jsx}}"), "*.{ts,{js,jsx}}");
+    assert_eq!(normalize_glob("\\{b,a}"), "\\{b,a}");
+  }
+
+  #[test]
+  fn matches_negated_globs() {
+    let matcher = GlobMatcher::new("!*.js");
+
+    assert!(!matcher.is_match("a.js"));
+    assert!(matcher.is_match("a.css"));
+  }
+
+  #[cfg(feature = "fast-glob")]
+  #[test]
+  fn agrees_with_glob_match() {
+    let pieces = [
+      "!",
+      "*",
+      "**",
+      "**/",
+      "/**",
+      "?",
+      "/",
+      ".",
+      "-",
+      ",",
+      "}",
+      "\\*",
+      "a",
+      "b",
+      "x",
+      "src",
+      ".js",
+      "[a-c]",
+      "[ab]",
+      "[!a]",
+      "[a-]",
+      "[]a]",
+      "[ab",
+      "a]",
+      "{a,b}",
+      "{a,b,}",
+      "{,a}",
+      "{b,a.b}",
+      "{a,a.b}",
+      "{*,b}",
+      "{a/b,c}",
+      "{a,{b,c}}",
+      ".{js,jsx}",
+      "*.{js,mjs,jsm,jsx,es6,cjs,ts,tsx}",
+    ];
+
+    let path_pieces = ["", "a", "b", "c", "x", ".", "/", "js", "a.b"];
+
+    // Deterministic xorshift so that failures are reproducible
+    let mut seed: u64 = 987654321;
+    let mut random = |n: usize| {
+      seed ^= seed << 13;
+      seed ^= seed >> 7;
+      seed ^= seed << 17;
+      (seed % n as u64) as usize
+    };
+
+    for _ in 0..50000 {
+      let glob_len = 1 + random(5);
+      let glob: String = (0..glob_len)
+        .map(|_| pieces[random(pieces.len())])
+        .collect();
+
+      let matcher = GlobMatcher::new(&glob);
+      for _ in 0..8 {
+        let path_len = random(6);
+        let path: String = (0..path_len)
+          .map(|_| path_pieces[random(path_pieces.len())])
+          .collect();
+
+        assert_eq!(
+          matcher.is_match(&path),
+          glob_match(&glob, &path),
+          "glob {:?} disagrees with glob_match for path {:?}",
+          glob,
+          path
+        );
+      }
+    }
+  }
+}
@@ -1,4 +1,9 @@
+pub mod config_diff;
 pub mod config_error;
+#[cfg(feature = "http-extends")]
+pub mod config_fetcher;
+mod glob_matcher;
+pub mod merge_trace;
 pub mod parcel_config;
 #[cfg(test)]
 mod parcel_config_fixtures;
@@ -6,6 +11,14 @@ pub mod parcel_rc;
 pub mod parcel_rc_config_loader;
 mod partial_parcel_config;
 pub mod pipeline;
+pub mod plugin_registry;
+pub mod source_location;
 
 pub use parcel_config::ParcelConfig;
+pub use parcel_config::ParcelConfigBuilder;
+pub use parcel_config::PluginCategory;
 pub use parcel_config::PluginNode;
+pub use parcel_config::ResolvedPlugins;
+pub use parcel_config::ResolverOutcome;
+pub use plugin_registry::LoadedPlugin;
+pub use plugin_registry::PluginRegistry;
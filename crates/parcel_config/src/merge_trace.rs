@@ -0,0 +1,77 @@
+use std::path::Path;
+
+use indexmap::IndexMap;
+
+use super::parcel_config::PluginCategory;
+use super::parcel_config::PluginNode;
+
+/// The pipeline of a pattern before and after merging a config with a config it extends
+///
+/// A pattern that one of the configs does not define has an empty pipeline on that side, and a
+/// pattern removed with "null" has an empty merged pipeline.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct MergeStep {
+  pub pipeline: Vec<PluginNode>,
+  pub extended: Vec<PluginNode>,
+  pub merged: Vec<PluginNode>,
+}
+
+impl MergeStep {
+  /// Whether a "..." in the pipeline was filled with the extended plugins in this step
+  pub fn expands_spread(&self) -> bool {
+    self.pipeline.iter().any(|p| p.package_name == "...")
+      && !self.merged.iter().any(|p| p.package_name == "...")
+  }
+}
+
+/// Records how the pipeline of each pattern was merged through a chain of extended configs
+///
+/// Steps are listed in the order the merges happened. When a config is loaded, each extended config
+/// is merged with the configs it extends before it is merged into the config that extends it, so the
+/// deepest merges are listed first.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MergeTrace {
+  steps: IndexMap<(PluginCategory, String), Vec<MergeStep>>,
+}
+
+impl MergeTrace {
+  pub(crate) fn push(&mut self, category: PluginCategory, pattern: &str, step: MergeStep) {
+    self
+      .steps
+      .entry((category, String::from(pattern)))
+      .or_default()
+      .push(step);
+  }
+
+  /// Adds the steps of a later merge after the steps already recorded
+  pub fn append(&mut self, trace: MergeTrace) {
+    for (key, steps) in trace.steps {
+      self.steps.entry(key).or_default().extend(steps);
+    }
+  }
+
+  /// Returns the merge steps that involved a pattern
+  pub fn steps(&self, category: PluginCategory, pattern: &str) -> &[MergeStep] {
+    self
+      .steps
+      .get(&(category, String::from(pattern)))
+      .map_or(&[], |steps| steps.as_slice())
+  }
+
+  /// Returns the configs that contributed plugins to a pattern, in the order they were merged
+  pub fn contributors(&self, category: PluginCategory, pattern: &str) -> Vec<&Path> {
+    let mut contributors: Vec<&Path> = Vec::new();
+    for step in self.steps(category, pattern) {
+      for plugin in step.pipeline.iter().chain(step.extended.iter()) {
+        let path = plugin.resolve_from.as_path();
+        if !contributors.contains(&path) {
+          contributors.push(path);
+        }
+      }
+    }
+
+    contributors
+  }
+}
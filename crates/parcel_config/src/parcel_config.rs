@@ -1,32 +1,181 @@
+use std::cell::OnceCell;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
 
 use indexmap::IndexMap;
+use nodejs_semver::Range;
+use nodejs_semver::Version;
+use parcel_filesystem::search::find_ancestor_file;
+use parcel_filesystem::FileSystem;
+use parcel_package_manager::PackageManager;
+use parcel_package_manager::Resolution;
+use serde::Deserialize;
 
+use super::config_diff::changed_patterns;
+use super::config_diff::ConfigDiff;
 use super::config_error::ConfigError;
+use super::config_error::ConfigWarning;
+use super::glob_matcher::normalize_glob;
+use super::glob_matcher::GlobMatcher;
+use super::parcel_rc::ParcelRcFile;
 use super::partial_parcel_config::PartialParcelConfig;
-use super::pipeline::is_match;
+use super::partial_parcel_config::REMOVE_PATTERN;
+use super::pipeline::basename;
+use super::pipeline::matcher_matches;
+use super::pipeline::normalize_separators;
+use super::pipeline::pattern_matcher;
 use super::pipeline::PipelineMap;
+use super::plugin_registry::LoadedPlugin;
+use super::plugin_registry::PluginRegistry;
+
+/// Named pipelines with a built-in meaning
+///
+/// The "node" pipeline is reserved for Node builtins and cannot be used by any phase. The other
+/// pipelines are assigned to dependencies by Parcel, so only apply to phases that run on the
+/// assets and bundles created from those dependencies.
+///
+pub const RESERVED_PIPELINES: [&str; 4] = ["bundle-text", "data-url", "node", "url"];
+
+/// The phases where the reserved pipelines other than "node" may be used
+const RESERVED_PIPELINE_PHASES: [&str; 3] = ["compressors", "optimizers", "transformers"];
+
+/// A copy of the .parcelrc published as @parcel/config-default, compiled in for
+/// `ParcelConfig::default_config`
+const DEFAULT_PARCEL_RC: &str = include_str!("default_config.json");
+
+/// The transformer used for files without any matching transformers when copying is enabled
+const COPY_TRANSFORMER: &str = "@parcel/transformer-raw";
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct PluginNode {
   pub package_name: String,
   pub resolve_from: Rc<PathBuf>,
 }
 
+impl PluginNode {
+  /// Returns the transformer that copies assets verbatim, resolved from resolve_from
+  pub fn copy_transformer(resolve_from: Rc<PathBuf>) -> PluginNode {
+    PluginNode {
+      package_name: String::from(COPY_TRANSFORMER),
+      resolve_from,
+    }
+  }
+
+  /// Whether this is the transformer used for unmatched files when copying is enabled
+  pub fn is_copy_transformer(&self) -> bool {
+    self.package_name == COPY_TRANSFORMER
+  }
+
+  /// Whether both nodes refer to the same plugin
+  ///
+  /// A package name refers to the same package wherever it is listed, but a relative specifier
+  /// such as `./reporter.js` only refers to the same file when resolved from the same directory.
+  pub(crate) fn is_same_plugin(&self, other: &PluginNode) -> bool {
+    self.package_name == other.package_name
+      && (!self.package_name.starts_with('.')
+        || self.resolve_from.parent() == other.resolve_from.parent())
+  }
+}
+
+/// The fields of a plugin's package.json that are used to check its compatibility
+#[derive(Deserialize)]
+struct PluginPackageJson {
+  engines: Option<PluginEngines>,
+}
+
+#[derive(Deserialize)]
+struct PluginEngines {
+  parcel: Option<String>,
+}
+
+/// The phases of a config that plugins are listed under
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PluginCategory {
+  Bundler,
+  Compressor,
+  Namer,
+  Optimizer,
+  Packager,
+  Reporter,
+  Resolver,
+  Runtime,
+  Transformer,
+  Validator,
+}
+
+impl PluginCategory {
+  /// Every category, in the order they appear in a .parcelrc
+  pub const ALL: [PluginCategory; 10] = [
+    PluginCategory::Bundler,
+    PluginCategory::Compressor,
+    PluginCategory::Namer,
+    PluginCategory::Optimizer,
+    PluginCategory::Packager,
+    PluginCategory::Reporter,
+    PluginCategory::Resolver,
+    PluginCategory::Runtime,
+    PluginCategory::Transformer,
+    PluginCategory::Validator,
+  ];
+}
+
+/// Resolved plugin paths, keyed by package name and the file the plugin is resolved from
+///
+/// The cache is not part of a config's value, so it is ignored when comparing configs.
+#[derive(Debug, Default)]
+pub(crate) struct PluginPathCache(RefCell<HashMap<(String, PathBuf), PathBuf>>);
+
+impl PartialEq for PluginPathCache {
+  fn eq(&self, _other: &Self) -> bool {
+    true
+  }
+}
+
+/// Matchers for the packager patterns, in the same order as the packagers, built on first use
+///
+/// Like the plugin path cache, the matchers are ignored when comparing configs.
+#[derive(Debug, Default)]
+pub(crate) struct PackagerMatchers(OnceCell<Vec<(String, GlobMatcher)>>);
+
+impl PartialEq for PackagerMatchers {
+  fn eq(&self, _other: &Self) -> bool {
+    true
+  }
+}
+
 /// Represents a fully merged and validated .parcel_rc config
+///
+/// With the `serde` feature enabled, the config can be serialized to be cached or sent to workers.
+/// Each plugin's resolve_from path is serialized separately, so deserializing does not share it
+/// between plugins.
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct ParcelConfig {
   pub(crate) bundler: PluginNode,
   pub(crate) compressors: PipelineMap,
+  /// Whether files without matching transformers are copied rather than reported as errors
+  pub(crate) default_to_copy: bool,
+  /// How many extends each config file is from the loaded config, which is at depth 0
+  pub(crate) extends_depths: HashMap<PathBuf, usize>,
+  /// Problems with the "extends" of the config files, reported by `validate`
+  pub(crate) extends_warnings: Vec<ConfigWarning>,
   pub(crate) namers: Vec<PluginNode>,
   pub(crate) optimizers: PipelineMap,
+  #[cfg_attr(feature = "serde", serde(skip))]
+  pub(crate) packager_matchers: PackagerMatchers,
   pub(crate) packagers: IndexMap<String, PluginNode>,
+  #[cfg_attr(feature = "serde", serde(skip))]
+  pub(crate) plugin_paths: PluginPathCache,
   pub(crate) reporters: Vec<PluginNode>,
   pub(crate) resolvers: Vec<PluginNode>,
   pub(crate) runtimes: Vec<PluginNode>,
   pub(crate) transformers: PipelineMap,
+  /// The phase and pattern of pipelines that were only "..." with nothing to fill it
+  pub(crate) unsatisfied_spreads: Vec<(String, String)>,
   pub(crate) validators: PipelineMap,
 }
 
@@ -42,15 +191,51 @@ impl TryFrom<PartialParcelConfig> for ParcelConfig {
         .collect()
     }
 
+    // Patterns removed with "null" are only dropped while merging, so a config that merged with
+    // nothing, or the base of a chain, can still hold the marker here
     fn filter_out_extends_from_map(
       map: IndexMap<String, Vec<PluginNode>>,
     ) -> IndexMap<String, Vec<PluginNode>> {
       map
         .into_iter()
+        .filter(|(_pattern, plugins)| {
+          !matches!(plugins.as_slice(), [plugin] if plugin.package_name == REMOVE_PATTERN)
+        })
         .map(|(pattern, plugins)| (pattern, filter_out_extends(plugins)))
         .collect()
     }
 
+    // A plugin listed both by a config and by a config it spreads with ... would otherwise run twice,
+    // so only its first occurrence is kept. Transformer pipelines may intentionally repeat a plugin,
+    // so they are not deduplicated. Relative plugins from different directories are different
+    // files, so they are kept.
+    fn dedup_plugins(plugins: Vec<PluginNode>) -> Vec<PluginNode> {
+      let mut deduped: Vec<PluginNode> = Vec::new();
+      for plugin in plugins {
+        if !deduped.iter().any(|p| p.is_same_plugin(&plugin)) {
+          deduped.push(plugin);
+        }
+      }
+
+      deduped
+    }
+
+    // A pipeline that is only ... relies entirely on extended configs, so it is likely a mistake
+    // when none of them filled it. A ... alongside other plugins is left to be a noop.
+    let mut unsatisfied_spreads = Vec::new();
+    for (phase, map) in [
+      ("compressors", &config.compressors),
+      ("optimizers", &config.optimizers),
+      ("transformers", &config.transformers),
+      ("validators", &config.validators),
+    ] {
+      for (pattern, plugins) in map {
+        if matches!(plugins.as_slice(), [plugin] if plugin.package_name == "...") {
+          unsatisfied_spreads.push((String::from(phase), pattern.clone()));
+        }
+      }
+    }
+
     let mut missing_phases = Vec::new();
 
     if let None = config.bundler {
@@ -62,7 +247,7 @@ impl TryFrom<PartialParcelConfig> for ParcelConfig {
       missing_phases.push(String::from("namers"));
     }
 
-    let resolvers = filter_out_extends(config.resolvers);
+    let resolvers = dedup_plugins(filter_out_extends(config.resolvers));
     if resolvers.is_empty() {
       missing_phases.push(String::from("resolvers"));
     }
@@ -77,19 +262,457 @@ impl TryFrom<PartialParcelConfig> for ParcelConfig {
     Ok(ParcelConfig {
       bundler: config.bundler.unwrap(),
       compressors: PipelineMap::new(filter_out_extends_from_map(config.compressors)),
+      default_to_copy: false,
+      extends_depths: config.extends_depths,
+      extends_warnings: config.extends_warnings,
       namers,
       optimizers: PipelineMap::new(filter_out_extends_from_map(config.optimizers)),
-      packagers: config.packagers,
-      reporters: filter_out_extends(config.reporters),
+      packagers: config
+        .packagers
+        .into_iter()
+        .filter(|(_pattern, plugin)| plugin.package_name != REMOVE_PATTERN)
+        .collect(),
+      packager_matchers: PackagerMatchers::default(),
+      plugin_paths: PluginPathCache::default(),
+      reporters: dedup_plugins(filter_out_extends(config.reporters)),
       resolvers,
-      runtimes: filter_out_extends(config.runtimes),
+      runtimes: dedup_plugins(filter_out_extends(config.runtimes)),
       transformers: PipelineMap::new(filter_out_extends_from_map(config.transformers)),
+      unsatisfied_spreads,
       validators: PipelineMap::new(filter_out_extends_from_map(config.validators)),
     })
   }
 }
 
+/// The plugins that apply to a single file, in the order of the phases they run in
+#[derive(Debug, PartialEq)]
+pub struct ResolvedPlugins {
+  pub resolvers: Vec<PluginNode>,
+  pub transformers: Vec<PluginNode>,
+  pub validators: Vec<PluginNode>,
+  pub optimizers: Vec<PluginNode>,
+  /// The packager for the path, which may be missing when transformers change the file type
+  pub packager: Option<PluginNode>,
+  pub compressors: Vec<PluginNode>,
+}
+
+/// The result of running a single resolver for a specifier
+#[derive(Debug)]
+pub enum ResolverOutcome {
+  /// The resolver resolved the specifier
+  Resolved(Resolution),
+  /// The resolver does not handle the specifier, so the next resolver is tried
+  Deferred,
+  /// The resolver handles the specifier, but could not resolve it
+  Failed(Box<dyn std::error::Error>),
+}
+
+/// Assembles a ParcelConfig programmatically, without loading a .parcelrc
+///
+/// Patterns are matched in the order they are added. The same validation as loading a .parcelrc
+/// is applied when building, so required phases must be provided.
+///
+#[derive(Default)]
+pub struct ParcelConfigBuilder {
+  config: PartialParcelConfig,
+}
+
+impl ParcelConfigBuilder {
+  pub fn bundler(mut self, bundler: PluginNode) -> Self {
+    self.config.bundler = Some(bundler);
+    self
+  }
+
+  pub fn compressors(mut self, pattern: &str, compressors: Vec<PluginNode>) -> Self {
+    self
+      .config
+      .compressors
+      .insert(String::from(pattern), compressors);
+    self
+  }
+
+  pub fn namers(mut self, namers: Vec<PluginNode>) -> Self {
+    self.config.namers = namers;
+    self
+  }
+
+  pub fn optimizers(mut self, pattern: &str, optimizers: Vec<PluginNode>) -> Self {
+    self
+      .config
+      .optimizers
+      .insert(String::from(pattern), optimizers);
+    self
+  }
+
+  pub fn packager(mut self, pattern: &str, packager: PluginNode) -> Self {
+    self
+      .config
+      .packagers
+      .insert(String::from(pattern), packager);
+    self
+  }
+
+  pub fn reporters(mut self, reporters: Vec<PluginNode>) -> Self {
+    self.config.reporters = reporters;
+    self
+  }
+
+  pub fn resolvers(mut self, resolvers: Vec<PluginNode>) -> Self {
+    self.config.resolvers = resolvers;
+    self
+  }
+
+  pub fn runtimes(mut self, runtimes: Vec<PluginNode>) -> Self {
+    self.config.runtimes = runtimes;
+    self
+  }
+
+  pub fn transformers(mut self, pattern: &str, transformers: Vec<PluginNode>) -> Self {
+    self
+      .config
+      .transformers
+      .insert(String::from(pattern), transformers);
+    self
+  }
+
+  pub fn validators(mut self, pattern: &str, validators: Vec<PluginNode>) -> Self {
+    self
+      .config
+      .validators
+      .insert(String::from(pattern), validators);
+    self
+  }
+
+  pub fn build(self) -> Result<ParcelConfig, ConfigError> {
+    ParcelConfig::try_from(self.config)
+  }
+}
+
 impl ParcelConfig {
+  pub fn builder() -> ParcelConfigBuilder {
+    ParcelConfigBuilder::default()
+  }
+
+  /// Sets whether files without matching transformers are copied verbatim
+  ///
+  /// When enabled, `transformers` returns `PluginNode::copy_transformer` for unmatched files
+  /// instead of an error. Files matched by an explicitly empty pipeline are still an error.
+  ///
+  pub fn with_default_to_copy(mut self, default_to_copy: bool) -> Self {
+    self.default_to_copy = default_to_copy;
+    self
+  }
+
+  /// Replaces the bundler of an already loaded config
+  pub fn with_bundler(mut self, node: PluginNode) -> Self {
+    self.bundler = node;
+    self
+  }
+
+  /// Replaces the transformers of a pattern in an already loaded config
+  ///
+  /// Patterns that are not in the config yet are added after the existing patterns, so they only
+  /// apply to files that no earlier pattern matches.
+  ///
+  pub fn with_transformer(mut self, pattern: &str, transformers: Vec<PluginNode>) -> Self {
+    self.transformers = self.transformers.with_pipeline(pattern, transformers);
+    self
+      .unsatisfied_spreads
+      .retain(|(phase, spread_pattern)| phase != "transformers" || spread_pattern != pattern);
+
+    self
+  }
+
+  /// Returns the config from @parcel/config-default without resolving the package from disk
+  ///
+  /// The plugins are resolved from resolve_from, which should be an absolute path such as a file
+  /// within the project root, so the plugin packages still need to be installed where they can be
+  /// found from there to build with this config.
+  ///
+  pub fn default_config(resolve_from: &Path) -> ParcelConfig {
+    let parcel_rc = ParcelRcFile {
+      path: PathBuf::from(resolve_from),
+      contents: serde_json5::from_str(DEFAULT_PARCEL_RC)
+        .expect("The embedded default config should be valid"),
+    };
+
+    PartialParcelConfig::try_from(&parcel_rc)
+      .and_then(ParcelConfig::try_from)
+      .expect("The embedded default config should be valid")
+  }
+
+  /// Returns every plugin in a category, in config order
+  ///
+  /// Plugins of categories that are configured by pattern are listed for all patterns, so a
+  /// plugin used by several patterns is listed once for each of them.
+  ///
+  pub fn plugins(&self, category: PluginCategory) -> Vec<&PluginNode> {
+    self
+      .patterned_plugins(category)
+      .into_iter()
+      .map(|(_pattern, plugin)| plugin)
+      .collect()
+  }
+
+  /// Returns every plugin in a category with the pattern it is configured for
+  ///
+  /// Categories that are not configured by pattern use an empty pattern.
+  fn patterned_plugins(&self, category: PluginCategory) -> Vec<(&str, &PluginNode)> {
+    fn unpatterned(plugins: &[PluginNode]) -> Vec<(&str, &PluginNode)> {
+      plugins.iter().map(|plugin| ("", plugin)).collect()
+    }
+
+    fn patterned(map: &PipelineMap) -> Vec<(&str, &PluginNode)> {
+      map
+        .entries()
+        .flat_map(|(pattern, plugins)| plugins.iter().map(|plugin| (pattern.as_str(), plugin)))
+        .collect()
+    }
+
+    match category {
+      PluginCategory::Bundler => vec![("", &self.bundler)],
+      PluginCategory::Compressor => patterned(&self.compressors),
+      PluginCategory::Namer => unpatterned(&self.namers),
+      PluginCategory::Optimizer => patterned(&self.optimizers),
+      PluginCategory::Packager => self
+        .packagers
+        .iter()
+        .map(|(pattern, plugin)| (pattern.as_str(), plugin))
+        .collect(),
+      PluginCategory::Reporter => unpatterned(&self.reporters),
+      PluginCategory::Resolver => unpatterned(&self.resolvers),
+      PluginCategory::Runtime => unpatterned(&self.runtimes),
+      PluginCategory::Transformer => patterned(&self.transformers),
+      PluginCategory::Validator => patterned(&self.validators),
+    }
+  }
+
+  /// Lists each plugin with its category and the .parcelrc file that defined it
+  ///
+  /// Plugins are listed in config order, with a plugin used by several patterns listed once.
+  ///
+  pub fn provenance(&self) -> Vec<(PluginCategory, String, PathBuf)> {
+    let plugins = PluginCategory::ALL.into_iter().flat_map(|category| {
+      self
+        .plugins(category)
+        .into_iter()
+        .map(move |p| (category, p))
+    });
+
+    let mut provenance = Vec::new();
+    for (category, plugin) in plugins {
+      let entry = (
+        category,
+        plugin.package_name.clone(),
+        PathBuf::from(plugin.resolve_from.as_path()),
+      );
+
+      if !provenance.contains(&entry) {
+        provenance.push(entry);
+      }
+    }
+
+    provenance
+  }
+
+  /// Lists each plugin in a category with how many extends its config is from the loaded config
+  ///
+  /// Plugins of the loaded config have a depth of 0, and plugins of configs it extends have a
+  /// depth of 1. A plugin used by several patterns is listed once.
+  ///
+  pub fn transitive_plugins(&self, category: PluginCategory) -> Vec<(PluginNode, usize)> {
+    let mut plugins: Vec<(PluginNode, usize)> = Vec::new();
+    for plugin in self.plugins(category) {
+      if plugins.iter().any(|(p, _depth)| p == plugin) {
+        continue;
+      }
+
+      let depth = self
+        .extends_depths
+        .get(plugin.resolve_from.as_path())
+        .copied()
+        .unwrap_or(0);
+
+      plugins.push((plugin.clone(), depth));
+    }
+
+    plugins
+  }
+
+  /// Renders the config back into .parcelrc syntax
+  ///
+  /// The output has no extends, as the plugins of extended configs are already merged in, and no
+  /// "..." entries, since those were filled when merging. Reparsing the output gives a config with
+  /// the same plugins, all resolved from the file the output is parsed from.
+  ///
+  pub fn to_parcel_rc_string(&self) -> String {
+    fn quote(value: &str) -> String {
+      let mut quoted = String::from('"');
+      for c in value.chars() {
+        match c {
+          '"' => quoted.push_str("\\\""),
+          '\\' => quoted.push_str("\\\\"),
+          c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+          c => quoted.push(c),
+        }
+      }
+
+      quoted.push('"');
+      quoted
+    }
+
+    fn list(plugins: &[PluginNode]) -> String {
+      let names: Vec<String> = plugins.iter().map(|p| quote(&p.package_name)).collect();
+      format!("[{}]", names.join(", "))
+    }
+
+    fn object<'a>(entries: impl Iterator<Item = (&'a String, String)>) -> String {
+      let fields: Vec<String> = entries
+        .map(|(pattern, value)| format!("    {}: {}", quote(pattern), value))
+        .collect();
+
+      if fields.is_empty() {
+        String::from("{}")
+      } else {
+        format!("{{\n{}\n  }}", fields.join(",\n"))
+      }
+    }
+
+    let pipelines =
+      |map: &PipelineMap| object(map.entries().map(|(p, plugins)| (p, list(plugins))));
+
+    let fields = [
+      ("bundler", quote(&self.bundler.package_name)),
+      ("compressors", pipelines(&self.compressors)),
+      ("namers", list(&self.namers)),
+      ("optimizers", pipelines(&self.optimizers)),
+      (
+        "packagers",
+        object(
+          self
+            .packagers
+            .iter()
+            .map(|(pattern, plugin)| (pattern, quote(&plugin.package_name))),
+        ),
+      ),
+      ("reporters", list(&self.reporters)),
+      ("resolvers", list(&self.resolvers)),
+      ("runtimes", list(&self.runtimes)),
+      ("transformers", pipelines(&self.transformers)),
+      ("validators", pipelines(&self.validators)),
+    ];
+
+    let fields: Vec<String> = fields
+      .into_iter()
+      .map(|(key, value)| format!("  {}: {}", quote(key), value))
+      .collect();
+
+    format!("{{\n{}\n}}\n", fields.join(",\n"))
+  }
+
+  /// Resolves a plugin to the path of its module, caching the result for later lookups
+  pub fn resolve_plugin(
+    &self,
+    node: &PluginNode,
+    package_manager: &impl PackageManager,
+  ) -> Result<PathBuf, ConfigError> {
+    let key = (node.package_name.clone(), node.resolve_from.to_path_buf());
+    if let Some(path) = self.plugin_paths.0.borrow().get(&key) {
+      return Ok(path.clone());
+    }
+
+    let resolved = package_manager
+      .resolve(&node.package_name, &node.resolve_from)
+      .map_err(|source| ConfigError::UnresolvedConfig {
+        config_type: String::from("plugin"),
+        from: key.1.clone(),
+        source: Box::new(source),
+        specifier: key.0.clone(),
+      })?
+      .resolved;
+
+    self
+      .plugin_paths
+      .0
+      .borrow_mut()
+      .insert(key, resolved.clone());
+
+    Ok(resolved)
+  }
+
+  /// Loads a plugin from the registry when its package name is registered, and otherwise
+  /// resolves it through the package manager
+  pub fn load_plugin<P>(
+    &self,
+    node: &PluginNode,
+    registry: &PluginRegistry<P>,
+    package_manager: &impl PackageManager,
+  ) -> Result<LoadedPlugin<P>, ConfigError> {
+    match registry.create(&node.package_name) {
+      Some(plugin) => Ok(LoadedPlugin::InProcess(plugin)),
+      None => self
+        .resolve_plugin(node, package_manager)
+        .map(LoadedPlugin::Resolved),
+    }
+  }
+
+  /// Checks that each plugin supports the given version of Parcel
+  ///
+  /// The supported range is read from `engines.parcel` in the package.json of each resolved
+  /// plugin. Relative plugins, plugins that do not declare a range, and plugins that cannot be
+  /// resolved or read are skipped, as there is nothing to check them against.
+  ///
+  pub fn check_plugin_versions(
+    &self,
+    fs: &impl FileSystem,
+    package_manager: &impl PackageManager,
+    version: &str,
+  ) -> Vec<ConfigWarning> {
+    let Ok(parsed_version) = Version::parse(version) else {
+      return Vec::new();
+    };
+
+    let mut checked: Vec<&PluginNode> = Vec::new();
+    let mut warnings = Vec::new();
+    for category in PluginCategory::ALL {
+      for plugin in self.plugins(category) {
+        if plugin.package_name.starts_with('.') || checked.contains(&plugin) {
+          continue;
+        }
+
+        checked.push(plugin);
+
+        let range = self
+          .resolve_plugin(plugin, package_manager)
+          .ok()
+          .and_then(|resolved| {
+            let from = resolved.parent().unwrap_or(&resolved);
+            // The root of the resolved path, such as a drive on Windows, bounds the search
+            let root = from.ancestors().last().unwrap_or(from);
+            find_ancestor_file(fs, vec![String::from("package.json")], from, root)
+          })
+          .and_then(|package_json| fs.read_to_string(package_json).ok())
+          .and_then(|contents| serde_json5::from_str::<PluginPackageJson>(&contents).ok())
+          .and_then(|package_json| package_json.engines?.parcel);
+
+        let Some(range) = range else {
+          continue;
+        };
+
+        let is_compatible = Range::parse(&range).map_or(true, |r| r.satisfies(&parsed_version));
+        if !is_compatible {
+          warnings.push(ConfigWarning::IncompatiblePlugin {
+            package_name: plugin.package_name.clone(),
+            range,
+            version: String::from(version),
+          });
+        }
+      }
+    }
+
+    warnings
+  }
+
   pub fn validators(&self, path: &Path) -> Result<Vec<PluginNode>, ConfigError> {
     let pipeline: &Option<&str> = &None;
     let validators = self.validators.get(path, pipeline);
@@ -97,6 +720,21 @@ impl ParcelConfig {
     Ok(validators)
   }
 
+  /// Returns the file the config was loaded from, which the copy transformer is resolved from
+  ///
+  /// Configs that were not loaded from a file use the file their bundler is resolved from.
+  ///
+  fn config_file(&self) -> Rc<PathBuf> {
+    self
+      .extends_depths
+      .iter()
+      .find(|(_path, depth)| **depth == 0)
+      .map_or_else(
+        || Rc::clone(&self.bundler.resolve_from),
+        |(path, _depth)| Rc::new(path.clone()),
+      )
+  }
+
   pub fn transformers(
     &self,
     path: &Path,
@@ -110,6 +748,18 @@ impl ParcelConfig {
         return Ok(Vec::new());
       }
 
+      // A pipeline that was emptied on purpose is reported rather than copied over
+      if let Some(pattern) = self.transformers.find_empty_pattern(path, pipeline) {
+        return Err(ConfigError::EmptyPipeline {
+          phase: String::from("transformers"),
+          pattern: String::from(pattern),
+        });
+      }
+
+      if self.default_to_copy {
+        return Ok(vec![PluginNode::copy_transformer(self.config_file())]);
+      }
+
       return Err(ConfigError::MissingPlugin {
         path: PathBuf::from(path),
         phase: String::from("transformers"),
@@ -120,6 +770,14 @@ impl ParcelConfig {
     Ok(transformers)
   }
 
+  /// Returns whether any transformers match the path, without collecting them
+  ///
+  /// This is equivalent to checking whether `transformers` returns an empty list with
+  /// `allow_empty` set.
+  pub fn has_transformers(&self, path: &Path, pipeline: &Option<impl AsRef<str>>) -> bool {
+    self.transformers.find_first(path, pipeline).is_some()
+  }
+
   pub fn bundler<P: AsRef<str>>(&self) -> Result<&PluginNode, ConfigError> {
     Ok(&self.bundler)
   }
@@ -132,13 +790,23 @@ impl ParcelConfig {
     Ok(&self.runtimes)
   }
 
+  /// Returns the runtimes that apply to bundles built for an environment
+  ///
+  /// Runtimes do not declare which environments they apply to yet, so every runtime is returned.
+  /// The environment type is generic as this crate does not depend on parcel_core.
+  ///
+  pub fn runtimes_for<E>(&self, _env: &E) -> Vec<PluginNode> {
+    self.runtimes.clone()
+  }
+
   pub fn packager(&self, path: &Path) -> Result<&PluginNode, ConfigError> {
-    let basename = path.file_name().unwrap().to_str().unwrap();
-    let path_str = path.as_os_str().to_str().unwrap();
+    let path_str = normalize_separators(path);
+    let basename = basename(&path_str);
     let packager = self
-      .packagers
+      .packager_matchers()
       .iter()
-      .find(|(pattern, _)| is_match(pattern, path_str, basename, ""));
+      .zip(self.packagers.values())
+      .find(|(matcher, _)| matcher_matches(matcher, &path_str, basename, ""));
 
     match packager {
       None => Err(ConfigError::MissingPlugin {
@@ -150,6 +818,16 @@ impl ParcelConfig {
     }
   }
 
+  fn packager_matchers(&self) -> &[(String, GlobMatcher)] {
+    self.packager_matchers.0.get_or_init(|| {
+      self
+        .packagers
+        .keys()
+        .map(|pattern| pattern_matcher(pattern))
+        .collect()
+    })
+  }
+
   pub fn optimizers(
     &self,
     path: &Path,
@@ -190,15 +868,259 @@ impl ParcelConfig {
     Ok(&self.resolvers)
   }
 
+  /// Runs the resolvers in order until one of them does not defer
+  ///
+  /// The invoke callback runs a resolver for the specifier and the path it is imported from.
+  /// Resolvers after the first that resolves or fails are not run.
+  ///
+  pub fn resolve_with(
+    &self,
+    specifier: &str,
+    from: &Path,
+    invoke: impl Fn(&PluginNode, &str, &Path) -> ResolverOutcome,
+  ) -> Result<Resolution, ConfigError> {
+    for resolver in self.resolvers.iter() {
+      match invoke(resolver, specifier, from) {
+        ResolverOutcome::Resolved(resolution) => return Ok(resolution),
+        ResolverOutcome::Deferred => continue,
+        ResolverOutcome::Failed(source) => {
+          return Err(ConfigError::UnresolvedSpecifier {
+            from: PathBuf::from(from),
+            resolver: resolver.package_name.clone(),
+            specifier: String::from(specifier),
+            source,
+          })
+        }
+      }
+    }
+
+    Err(ConfigError::UnhandledSpecifier {
+      from: PathBuf::from(from),
+      specifier: String::from(specifier),
+    })
+  }
+
   pub fn reporters(&self) -> Result<&Vec<PluginNode>, ConfigError> {
     Ok(&self.reporters)
   }
+
+  /// Returns every plugin that would be used for the given path and named pipeline
+  pub fn resolve_plugins_for(
+    &self,
+    path: &Path,
+    pipeline: &Option<impl AsRef<str>>,
+  ) -> Result<ResolvedPlugins, ConfigError> {
+    Ok(ResolvedPlugins {
+      resolvers: self.resolvers()?.clone(),
+      transformers: self.transformers(path, pipeline, false)?,
+      validators: self.validators(path)?,
+      optimizers: self.optimizers(path, pipeline)?,
+      packager: self.packager(path).ok().cloned(),
+      compressors: self.compressors(path)?,
+    })
+  }
+
+  /// Checks the config for likely mistakes that do not prevent it from being used
+  ///
+  /// Named pipelines in optimizers and compressors can only apply to bundles whose entry assets
+  /// were transformed with the same pipeline, so pipelines missing from transformers are
+  /// usually typos. Reserved pipelines used in phases where they have no meaning, and pipelines
+  /// that only inherited with "..." from configs that provided nothing, are also reported, along
+  /// with configs that were extended more than once by the same file.
+  ///
+  pub fn validate(&self) -> Vec<ConfigWarning> {
+    let transformer_pipelines = self.transformers.named_pipelines();
+    let mut warnings = self.extends_warnings.clone();
+    warnings.append(&mut self.validate_reserved_pipelines());
+
+    for (phase, map) in [
+      ("optimizers", &self.optimizers),
+      ("compressors", &self.compressors),
+    ] {
+      let mut pipelines: Vec<String> = Vec::new();
+      for pipeline in map.named_pipelines() {
+        if !transformer_pipelines.contains(&pipeline) && !pipelines.iter().any(|p| p == pipeline) {
+          pipelines.push(String::from(pipeline));
+        }
+      }
+
+      if !pipelines.is_empty() {
+        warnings.push(ConfigWarning::OrphanPipelines {
+          phase: String::from(phase),
+          pipelines,
+        });
+      }
+    }
+
+    for (phase, pattern) in &self.unsatisfied_spreads {
+      warnings.push(ConfigWarning::UnsatisfiedSpread {
+        phase: phase.clone(),
+        pattern: pattern.clone(),
+      });
+    }
+
+    warnings
+  }
+
+  /// Finds reserved pipelines that are used in phases where they have no meaning
+  fn validate_reserved_pipelines(&self) -> Vec<ConfigWarning> {
+    let packager_pipelines: Vec<&str> = self
+      .packagers
+      .keys()
+      .filter_map(|pattern| pattern.split_once(':').map(|(pipeline, _glob)| pipeline))
+      .collect();
+
+    let phases = [
+      ("compressors", self.compressors.named_pipelines()),
+      ("optimizers", self.optimizers.named_pipelines()),
+      ("packagers", packager_pipelines),
+      ("transformers", self.transformers.named_pipelines()),
+      ("validators", self.validators.named_pipelines()),
+    ];
+
+    let mut warnings = Vec::new();
+    for (phase, pipelines) in phases {
+      for pipeline in pipelines {
+        let is_allowed = !RESERVED_PIPELINES.contains(&pipeline)
+          || (pipeline != "node" && RESERVED_PIPELINE_PHASES.contains(&phase));
+
+        let warning = ConfigWarning::ReservedPipeline {
+          phase: String::from(phase),
+          pipeline: String::from(pipeline),
+        };
+
+        if !is_allowed && !warnings.contains(&warning) {
+          warnings.push(warning);
+        }
+      }
+    }
+
+    warnings
+  }
+
+  /// Lists the patterns of each category that do not match any of the files
+  ///
+  /// Patterns are matched regardless of their named pipeline, so this helps to find patterns left
+  /// behind after files were moved or renamed.
+  ///
+  pub fn dead_patterns(&self, files: &[PathBuf]) -> Vec<(PluginCategory, String)> {
+    fn unmatched(map: &PipelineMap, files: &[PathBuf]) -> Vec<String> {
+      map
+        .match_files(files)
+        .into_iter()
+        .filter(|(_pattern, matched)| matched.is_empty())
+        .map(|(pattern, _matched)| String::from(pattern))
+        .collect()
+    }
+
+    let mut dead_patterns = Vec::new();
+    for category in PluginCategory::ALL {
+      let patterns = match category {
+        PluginCategory::Compressor => unmatched(&self.compressors, files),
+        PluginCategory::Optimizer => unmatched(&self.optimizers, files),
+        PluginCategory::Packager => self
+          .packager_matchers()
+          .iter()
+          .zip(self.packagers.keys())
+          .filter(|(matcher, _pattern)| {
+            let (pipeline, _glob) = matcher;
+            !files.iter().any(|file| {
+              let path = normalize_separators(file);
+              matcher_matches(matcher, &path, basename(&path), pipeline)
+            })
+          })
+          .map(|(_matcher, pattern)| pattern.clone())
+          .collect(),
+        PluginCategory::Transformer => unmatched(&self.transformers, files),
+        PluginCategory::Validator => unmatched(&self.validators, files),
+        _ => continue,
+      };
+
+      dead_patterns.extend(patterns.into_iter().map(|pattern| (category, pattern)));
+    }
+
+    dead_patterns
+  }
+
+  /// Compares two configs, treating plugins resolved from the same place within their project
+  /// roots as equal
+  ///
+  /// Configs loaded from copies of a project at different paths are not equal, as each plugin is
+  /// resolved from an absolute path. Comparing them structurally allows them to share a cache.
+  /// Plugins resolved from outside of the project root must have the same absolute path.
+  ///
+  pub fn structural_eq(
+    &self,
+    other: &ParcelConfig,
+    project_root: &Path,
+    other_root: &Path,
+  ) -> bool {
+    fn structure<'a>(
+      config: &'a ParcelConfig,
+      root: &Path,
+    ) -> Vec<(PluginCategory, String, &'a str, &'a Path)> {
+      PluginCategory::ALL
+        .into_iter()
+        .flat_map(|category| {
+          config
+            .patterned_plugins(category)
+            .into_iter()
+            .map(move |(pattern, plugin)| (category, pattern, plugin))
+        })
+        .map(|(category, pattern, plugin)| {
+          let resolve_from = plugin.resolve_from.as_path();
+          (
+            category,
+            normalize_glob(pattern),
+            plugin.package_name.as_str(),
+            resolve_from.strip_prefix(root).unwrap_or(resolve_from),
+          )
+        })
+        .collect()
+    }
+
+    self.default_to_copy == other.default_to_copy
+      && structure(self, project_root) == structure(other, other_root)
+  }
+
+  /// Compares the plugins of two configs
+  ///
+  /// This can be used when a .parcelrc is reloaded to only invalidate work affected by the
+  /// plugins that changed.
+  ///
+  pub fn diff(&self, other: &ParcelConfig) -> ConfigDiff {
+    ConfigDiff {
+      bundler: self.bundler != other.bundler,
+      compressors: self.compressors.changed_patterns(&other.compressors),
+      namers: self.namers != other.namers,
+      optimizers: self.optimizers.changed_patterns(&other.optimizers),
+      packagers: changed_patterns(&self.packagers, &other.packagers, |pattern| {
+        String::from(pattern)
+      }),
+      reporters: self.reporters != other.reporters,
+      resolvers: self.resolvers != other.resolvers,
+      runtimes: self.runtimes != other.runtimes,
+      transformers: self.transformers.changed_patterns(&other.transformers),
+      validators: self.validators.changed_patterns(&other.validators),
+    }
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::parcel_config_fixtures::config_builder;
+
+  fn plugin(package_name: &str) -> PluginNode {
+    PluginNode {
+      package_name: String::from(package_name),
+      resolve_from: Rc::new(PathBuf::from("/")),
+    }
+  }
+
   mod try_from {
+    use indexmap::indexmap;
+
     use super::*;
     use crate::partial_parcel_config::PartialParcelConfigBuilder;
 
@@ -243,5 +1165,1556 @@ mod tests {
 
       assert!(config.is_ok_and(|c| !c.resolvers.contains(&extension())));
     }
+
+    #[test]
+    fn removes_duplicate_plugins_from_flat_lists() {
+      let partial_config = PartialParcelConfigBuilder::default()
+        .bundler(Some(plugin("@parcel/bundler-default")))
+        .namers(vec![plugin("@parcel/namer-default")])
+        .reporters(vec![
+          plugin("@parcel/reporter-cli"),
+          plugin("@parcel/reporter-dev-server"),
+          plugin("@parcel/reporter-cli"),
+        ])
+        .resolvers(vec![
+          plugin("@parcel/resolver-default"),
+          plugin("@parcel/resolver-default"),
+        ])
+        .runtimes(vec![
+          plugin("@parcel/runtime-js"),
+          plugin("@parcel/runtime-js"),
+        ])
+        .transformers(indexmap! {
+          String::from("*.js") => vec![
+            plugin("@parcel/transformer-js"),
+            plugin("@parcel/transformer-js"),
+          ]
+        })
+        .build()
+        .unwrap();
+
+      let config = ParcelConfig::try_from(partial_config).unwrap();
+
+      assert_eq!(
+        config.reporters,
+        vec![
+          plugin("@parcel/reporter-cli"),
+          plugin("@parcel/reporter-dev-server"),
+        ]
+      );
+      assert_eq!(config.resolvers, vec![plugin("@parcel/resolver-default")]);
+      assert_eq!(config.runtimes, vec![plugin("@parcel/runtime-js")]);
+      assert_eq!(
+        config
+          .transformers
+          .get(Path::new("index.js"), &None::<&str>),
+        vec![
+          plugin("@parcel/transformer-js"),
+          plugin("@parcel/transformer-js"),
+        ]
+      );
+    }
+
+    #[test]
+    fn removes_duplicate_reporters_from_extended_configs() {
+      let base = PartialParcelConfigBuilder::default()
+        .reporters(vec![plugin("@parcel/reporter-cli")])
+        .build()
+        .unwrap();
+
+      let config = PartialParcelConfigBuilder::default()
+        .bundler(Some(plugin("@parcel/bundler-default")))
+        .namers(vec![plugin("@parcel/namer-default")])
+        .reporters(vec![plugin("..."), plugin("@parcel/reporter-cli")])
+        .resolvers(vec![plugin("@parcel/resolver-default")])
+        .build()
+        .unwrap();
+
+      let config = ParcelConfig::try_from(PartialParcelConfig::merge(config, base)).unwrap();
+
+      assert_eq!(config.reporters, vec![plugin("@parcel/reporter-cli")]);
+    }
+
+    #[test]
+    fn keeps_relative_plugins_from_other_directories() {
+      let relative_plugin = |resolve_from: &str| PluginNode {
+        package_name: String::from("./resolver.js"),
+        resolve_from: Rc::new(PathBuf::from(resolve_from)),
+      };
+
+      let base = PartialParcelConfigBuilder::default()
+        .resolvers(vec![
+          relative_plugin("/base/.parcelrc"),
+          relative_plugin("/app/shared.parcelrc"),
+        ])
+        .build()
+        .unwrap();
+
+      let config = PartialParcelConfigBuilder::default()
+        .bundler(Some(plugin("@parcel/bundler-default")))
+        .namers(vec![plugin("@parcel/namer-default")])
+        .resolvers(vec![relative_plugin("/app/.parcelrc"), plugin("...")])
+        .build()
+        .unwrap();
+
+      let config = ParcelConfig::try_from(PartialParcelConfig::merge(config, base)).unwrap();
+
+      assert_eq!(
+        config.resolvers,
+        vec![
+          relative_plugin("/app/.parcelrc"),
+          relative_plugin("/base/.parcelrc")
+        ]
+      );
+    }
+
+    #[test]
+    fn shares_resolve_from_between_plugins_from_one_file() {
+      let parcel_rc = ParcelRcFile {
+        path: PathBuf::from("/.parcelrc"),
+        contents: serde_json5::from_str(
+          r#"{
+            "bundler": "@parcel/bundler-default",
+            "namers": ["@parcel/namer-default"],
+            "resolvers": ["@parcel/resolver-default"],
+            "transformers": { "*.js": ["@parcel/transformer-js"] }
+          }"#,
+        )
+        .unwrap(),
+      };
+
+      let config = PartialParcelConfig::try_from(&parcel_rc)
+        .and_then(ParcelConfig::try_from)
+        .unwrap();
+
+      let transformers = config.transformers(Path::new("a.js"), &None::<&str>, false);
+      let resolve_from = &config.bundler.resolve_from;
+
+      assert!(Rc::ptr_eq(resolve_from, &config.namers[0].resolve_from));
+      assert!(Rc::ptr_eq(resolve_from, &config.resolvers[0].resolve_from));
+      assert!(Rc::ptr_eq(
+        resolve_from,
+        &transformers.unwrap()[0].resolve_from
+      ));
+    }
+
+    fn removed_patterns_config() -> PartialParcelConfig {
+      PartialParcelConfigBuilder::default()
+        .bundler(Some(plugin("@parcel/bundler-default")))
+        .compressors(indexmap! {
+          String::from("*.gz") => vec![plugin("null")],
+          String::from("*.br") => vec![plugin("@parcel/compressor-brotli")],
+        })
+        .namers(vec![plugin("@parcel/namer-default")])
+        .packagers(indexmap! {
+          String::from("*.js") => plugin("null"),
+          String::from("*.css") => plugin("@parcel/packager-css"),
+        })
+        .resolvers(vec![plugin("@parcel/resolver-default")])
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn removes_null_patterns_from_configs_without_extends() {
+      let config = ParcelConfig::try_from(removed_patterns_config()).unwrap();
+
+      assert_eq!(
+        config.compressors,
+        PipelineMap::new(indexmap! {
+          String::from("*.br") => vec![plugin("@parcel/compressor-brotli")],
+        })
+      );
+      assert_eq!(
+        config.packagers,
+        indexmap! { String::from("*.css") => plugin("@parcel/packager-css") }
+      );
+    }
+
+    #[test]
+    fn removes_null_patterns_from_a_base_config_without_a_parent() {
+      let config = PartialParcelConfigBuilder::default()
+        .compressors(indexmap! {
+          String::from("*.br") => vec![plugin("...")],
+        })
+        .build()
+        .unwrap();
+
+      let config = ParcelConfig::try_from(PartialParcelConfig::merge(
+        config,
+        removed_patterns_config(),
+      ))
+      .unwrap();
+
+      assert_eq!(
+        config.compressors,
+        PipelineMap::new(indexmap! {
+          String::from("*.br") => vec![plugin("@parcel/compressor-brotli")],
+        })
+      );
+      assert_eq!(
+        config.packagers,
+        indexmap! { String::from("*.css") => plugin("@parcel/packager-css") }
+      );
+    }
+  }
+
+  mod default_config {
+    use super::*;
+
+    #[test]
+    fn returns_the_embedded_default_config() {
+      let config = ParcelConfig::default_config(Path::new("/index"));
+      let resolve_from = Rc::new(PathBuf::from("/index"));
+      let plugin = |package_name: &str| PluginNode {
+        package_name: String::from(package_name),
+        resolve_from: Rc::clone(&resolve_from),
+      };
+
+      assert_eq!(config.bundler, plugin("@parcel/bundler-default"));
+      assert_eq!(config.resolvers, vec![plugin("@parcel/resolver-default")]);
+      assert_eq!(
+        config
+          .transformers
+          .get(Path::new("index.js"), &None::<&str>),
+        vec![
+          plugin("@parcel/transformer-babel"),
+          plugin("@parcel/transformer-js"),
+          plugin("@parcel/transformer-react-refresh-wrap"),
+        ]
+      );
+      assert_eq!(
+        config
+          .transformers
+          .get(Path::new("styles.css"), &None::<&str>),
+        vec![
+          plugin("@parcel/transformer-postcss"),
+          plugin("@parcel/transformer-css"),
+        ]
+      );
+    }
+  }
+
+  #[cfg(feature = "serde")]
+  mod serde {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_json() {
+      let config = ParcelConfig::default_config(Path::new("/index"));
+
+      let json = serde_json::to_string(&config).unwrap();
+      let deserialized: ParcelConfig = serde_json::from_str(&json).unwrap();
+
+      assert_eq!(deserialized, config);
+      assert_eq!(serde_json::to_string(&deserialized).unwrap(), json);
+    }
+  }
+
+  mod builder {
+    use super::*;
+
+    #[test]
+    fn builds_a_minimal_config() {
+      let config = config_builder()
+        .transformers("*.js", vec![plugin("@parcel/transformer-js")])
+        .build()
+        .map_err(|e| e.to_string());
+
+      assert_eq!(
+        config,
+        Ok(ParcelConfig {
+          bundler: plugin("@parcel/bundler-default"),
+          compressors: PipelineMap::default(),
+          default_to_copy: false,
+          extends_depths: HashMap::new(),
+          extends_warnings: Vec::new(),
+          namers: vec![plugin("@parcel/namer-default")],
+          optimizers: PipelineMap::default(),
+          packagers: IndexMap::new(),
+          packager_matchers: PackagerMatchers::default(),
+          plugin_paths: PluginPathCache::default(),
+          reporters: Vec::new(),
+          resolvers: vec![plugin("@parcel/resolver-default")],
+          runtimes: Vec::new(),
+          transformers: PipelineMap::new(IndexMap::from([(
+            String::from("*.js"),
+            vec![plugin("@parcel/transformer-js")]
+          )])),
+          unsatisfied_spreads: Vec::new(),
+          validators: PipelineMap::default(),
+        })
+      );
+    }
+
+    #[test]
+    fn returns_an_error_when_bundler_is_missing() {
+      let config = ParcelConfig::builder()
+        .namers(vec![plugin("@parcel/namer-default")])
+        .resolvers(vec![plugin("@parcel/resolver-default")])
+        .build()
+        .map_err(|e| e.to_string());
+
+      assert_eq!(
+        config,
+        Err(
+          ConfigError::InvalidConfig(format!(
+            "Missing plugins for the following phases: {:?}",
+            vec!("bundler")
+          ))
+          .to_string()
+        )
+      );
+    }
+  }
+
+  mod plugins {
+    use super::*;
+
+    #[test]
+    fn returns_the_plugins_of_each_category() {
+      let config = ParcelConfig::builder()
+        .bundler(plugin("@parcel/bundler-default"))
+        .compressors("*", vec![plugin("@parcel/compressor-raw")])
+        .namers(vec![plugin("@parcel/namer-default")])
+        .optimizers("*.js", vec![plugin("@parcel/optimizer-swc")])
+        .packager("*.js", plugin("@parcel/packager-js"))
+        .reporters(vec![plugin("@parcel/reporter-cli")])
+        .resolvers(vec![plugin("@parcel/resolver-default")])
+        .runtimes(vec![plugin("@parcel/runtime-js")])
+        .transformers("*.js", vec![plugin("@parcel/transformer-js")])
+        .transformers("*.ts", vec![plugin("@parcel/transformer-js")])
+        .validators("*.ts", vec![plugin("@parcel/validator-typescript")])
+        .build()
+        .unwrap();
+
+      let plugins: Vec<(PluginCategory, Vec<&str>)> = PluginCategory::ALL
+        .into_iter()
+        .map(|category| {
+          let names = config
+            .plugins(category)
+            .into_iter()
+            .map(|p| p.package_name.as_str())
+            .collect();
+
+          (category, names)
+        })
+        .collect();
+
+      assert_eq!(
+        plugins,
+        vec![
+          (PluginCategory::Bundler, vec!["@parcel/bundler-default"]),
+          (PluginCategory::Compressor, vec!["@parcel/compressor-raw"]),
+          (PluginCategory::Namer, vec!["@parcel/namer-default"]),
+          (PluginCategory::Optimizer, vec!["@parcel/optimizer-swc"]),
+          (PluginCategory::Packager, vec!["@parcel/packager-js"]),
+          (PluginCategory::Reporter, vec!["@parcel/reporter-cli"]),
+          (PluginCategory::Resolver, vec!["@parcel/resolver-default"]),
+          (PluginCategory::Runtime, vec!["@parcel/runtime-js"]),
+          (
+            PluginCategory::Transformer,
+            vec!["@parcel/transformer-js", "@parcel/transformer-js"]
+          ),
+          (
+            PluginCategory::Validator,
+            vec!["@parcel/validator-typescript"]
+          ),
+        ]
+      );
+    }
+  }
+
+  mod provenance {
+    use super::*;
+    use crate::parcel_config_fixtures::default_extended_config;
+
+    #[test]
+    fn returns_the_file_that_defined_each_plugin() {
+      let project_root = PathBuf::from("/project");
+      let fixture = default_extended_config(&project_root);
+      let base = fixture.base_config.path;
+      let extended = fixture.extended_config.path;
+
+      let entry = |category, package_name: &str, path: &PathBuf| {
+        (category, String::from(package_name), path.clone())
+      };
+
+      assert_eq!(
+        fixture.parcel_config.provenance(),
+        vec![
+          entry(
+            PluginCategory::Bundler,
+            "@parcel/bundler-default",
+            &extended
+          ),
+          entry(
+            PluginCategory::Compressor,
+            "@parcel/compressor-raw",
+            &extended
+          ),
+          entry(PluginCategory::Namer, "@parcel/namer-default", &extended),
+          entry(
+            PluginCategory::Optimizer,
+            "@parcel/optimizer-swc",
+            &extended
+          ),
+          entry(PluginCategory::Packager, "@parcel/packager-js", &extended),
+          entry(
+            PluginCategory::Reporter,
+            "@parcel/reporter-dev-server",
+            &extended
+          ),
+          entry(
+            PluginCategory::Reporter,
+            "@scope/parcel-reporter-metrics",
+            &base
+          ),
+          entry(
+            PluginCategory::Resolver,
+            "@parcel/resolver-default",
+            &extended
+          ),
+          entry(PluginCategory::Runtime, "@parcel/runtime-js", &extended),
+          entry(
+            PluginCategory::Transformer,
+            "@parcel/transformer-js",
+            &extended
+          ),
+          entry(
+            PluginCategory::Transformer,
+            "@scope/parcel-transformer-ts",
+            &base
+          ),
+        ]
+      );
+    }
+
+    #[test]
+    fn lists_plugins_used_by_several_patterns_once() {
+      let config = config_builder()
+        .transformers("*.js", vec![plugin("@parcel/transformer-js")])
+        .transformers("*.ts", vec![plugin("@parcel/transformer-js")])
+        .build()
+        .unwrap();
+
+      let transformers: Vec<_> = config
+        .provenance()
+        .into_iter()
+        .filter(|(category, ..)| *category == PluginCategory::Transformer)
+        .collect();
+
+      assert_eq!(
+        transformers,
+        vec![(
+          PluginCategory::Transformer,
+          String::from("@parcel/transformer-js"),
+          PathBuf::from("/")
+        )]
+      );
+    }
+  }
+
+  mod transitive_plugins {
+    use parcel_filesystem::in_memory_file_system::InMemoryFileSystem;
+    use parcel_package_manager::MockPackageManager;
+
+    use super::*;
+    use crate::parcel_rc_config_loader::LoadConfigOptions;
+    use crate::parcel_rc_config_loader::ParcelRcConfigLoader;
+
+    #[test]
+    fn returns_the_extends_depth_of_each_plugin() {
+      let project_root = PathBuf::from("/project");
+      let fs = InMemoryFileSystem::from_tree(
+        &project_root,
+        r#"
+          .parcelrc: { "extends": "./a.parcelrc", "transformers": { "*.js": ["parcel-transformer-root", "..."] } }
+          a.parcelrc: { "extends": "./b.parcelrc", "transformers": { "*.{js,ts}": ["@parcel/transformer-babel", "..."] } }
+          b.parcelrc: { "bundler": "@parcel/bundler-default", "namers": ["@parcel/namer-default"], "resolvers": ["@parcel/resolver-default"], "transformers": { "*.{js,ts}": ["@parcel/transformer-js"], "*.js": ["@parcel/transformer-js"] } }
+        "#,
+      );
+
+      let (config, _files) = ParcelRcConfigLoader::new(&fs, &MockPackageManager::new())
+        .load(&project_root, LoadConfigOptions::default())
+        .unwrap();
+
+      let plugin = |package_name: &str, file: &str| PluginNode {
+        package_name: String::from(package_name),
+        resolve_from: Rc::new(project_root.join(file)),
+      };
+
+      assert_eq!(
+        config.transitive_plugins(PluginCategory::Transformer),
+        vec![
+          (plugin("@parcel/transformer-babel", "a.parcelrc"), 1),
+          (plugin("@parcel/transformer-js", "b.parcelrc"), 2),
+          (plugin("parcel-transformer-root", ".parcelrc"), 0),
+        ]
+      );
+      assert_eq!(
+        config.transitive_plugins(PluginCategory::Bundler),
+        vec![(plugin("@parcel/bundler-default", "b.parcelrc"), 2)]
+      );
+    }
+  }
+
+  mod to_parcel_rc_string {
+    use super::*;
+    use crate::parcel_config_fixtures::default_extended_config;
+
+    fn reparse(path: &Path, contents: &str) -> ParcelConfig {
+      let parcel_rc = ParcelRcFile {
+        path: PathBuf::from(path),
+        contents: serde_json5::from_str(contents).unwrap(),
+      };
+
+      PartialParcelConfig::try_from(&parcel_rc)
+        .and_then(ParcelConfig::try_from)
+        .unwrap()
+    }
+
+    #[test]
+    fn renders_the_config() {
+      let config = config_builder()
+        .packager("*.js", plugin("@parcel/packager-js"))
+        .transformers(
+          "*.{js,ts}",
+          vec![
+            plugin("@parcel/transformer-babel"),
+            plugin("@parcel/transformer-js"),
+          ],
+        )
+        .build()
+        .unwrap();
+
+      assert_eq!(
+        config.to_parcel_rc_string(),
+        [
+          r#"{"#,
+          r#"  "bundler": "@parcel/bundler-default","#,
+          r#"  "compressors": {},"#,
+          r#"  "namers": ["@parcel/namer-default"],"#,
+          r#"  "optimizers": {},"#,
+          r#"  "packagers": {"#,
+          r#"    "*.js": "@parcel/packager-js""#,
+          r#"  },"#,
+          r#"  "reporters": [],"#,
+          r#"  "resolvers": ["@parcel/resolver-default"],"#,
+          r#"  "runtimes": [],"#,
+          r#"  "transformers": {"#,
+          r#"    "*.{js,ts}": ["@parcel/transformer-babel", "@parcel/transformer-js"]"#,
+          r#"  },"#,
+          r#"  "validators": {}"#,
+          r#"}"#,
+          r#""#,
+        ]
+        .join("\n")
+      );
+    }
+
+    #[test]
+    fn round_trips_a_config_from_one_file() {
+      let config = ParcelConfig::default_config(Path::new("/index"));
+      let output = config.to_parcel_rc_string();
+
+      assert_eq!(reparse(Path::new("/index"), &output), config);
+    }
+
+    #[test]
+    fn round_trips_an_extended_config() {
+      let project_root = PathBuf::from("/project");
+      let config = default_extended_config(&project_root).parcel_config;
+      let output = config.to_parcel_rc_string();
+
+      let path = project_root.join(".parcelrc");
+      let reparsed = reparse(&path, &output);
+      let package_names = |config: &ParcelConfig| -> Vec<(PluginCategory, String)> {
+        config
+          .provenance()
+          .into_iter()
+          .map(|(category, package_name, _path)| (category, package_name))
+          .collect()
+      };
+
+      assert!(!output.contains("..."));
+      assert_eq!(package_names(&reparsed), package_names(&config));
+      assert_eq!(reparsed.to_parcel_rc_string(), output);
+    }
+  }
+
+  mod resolve_plugin {
+    use mockall::predicate::eq;
+    use parcel_package_manager::MockPackageManager;
+    use parcel_package_manager::Resolution;
+    use parcel_package_manager::ResolveError;
+
+    use super::*;
+
+    #[test]
+    fn resolves_and_caches_plugin_paths() {
+      let config = ParcelConfig::default_config(Path::new("/index"));
+      let node = config.bundler.clone();
+
+      let mut package_manager = MockPackageManager::new();
+      package_manager
+        .expect_resolve()
+        .with(eq("@parcel/bundler-default"), eq(Path::new("/index")))
+        .times(1)
+        .returning(|_, _| {
+          Ok(Resolution {
+            resolved: PathBuf::from("/node_modules/@parcel/bundler-default/lib/index.js"),
+          })
+        });
+
+      for _ in 0..2 {
+        assert_eq!(
+          config
+            .resolve_plugin(&node, &package_manager)
+            .map_err(|e| e.to_string()),
+          Ok(PathBuf::from(
+            "/node_modules/@parcel/bundler-default/lib/index.js"
+          ))
+        );
+      }
+    }
+
+    #[test]
+    fn returns_an_error_for_unresolved_plugins() {
+      let config = ParcelConfig::default_config(Path::new("/index"));
+      let node = plugin("@parcel/bundler-missing");
+
+      let mut package_manager = MockPackageManager::new();
+      package_manager
+        .expect_resolve()
+        .returning(|specifier, from| {
+          Err(ResolveError::NotFound(
+            String::from(specifier),
+            from.display().to_string(),
+          ))
+        });
+
+      assert_eq!(
+        config
+          .resolve_plugin(&node, &package_manager)
+          .map_err(|e| e.to_string()),
+        Err(String::from(
+          "Failed to resolve plugin @parcel/bundler-missing from /"
+        ))
+      );
+    }
+  }
+
+  mod transformers {
+    use super::*;
+
+    fn config() -> ParcelConfig {
+      config_builder()
+        .transformers("*.js", vec![plugin("@parcel/transformer-js")])
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn errors_for_unmatched_files() {
+      assert_eq!(
+        config()
+          .transformers(Path::new("a.wasm"), &None::<&str>, false)
+          .map_err(|e| e.to_string()),
+        Err(
+          ConfigError::MissingPlugin {
+            path: PathBuf::from("a.wasm"),
+            phase: String::from("transformers"),
+            pipeline: None,
+          }
+          .to_string()
+        )
+      );
+    }
+
+    #[test]
+    fn errors_for_explicitly_empty_pipelines() {
+      let config = config().with_transformer("*.wasm", Vec::new());
+
+      assert_eq!(
+        config
+          .transformers(Path::new("a.wasm"), &None::<&str>, false)
+          .map_err(|e| e.to_string()),
+        Err(String::from(
+          "The transformers pipeline for *.wasm is empty"
+        ))
+      );
+      assert_eq!(
+        config
+          .transformers(Path::new("a.wasm"), &None::<&str>, true)
+          .unwrap(),
+        Vec::new()
+      );
+    }
+
+    #[test]
+    fn errors_for_explicitly_empty_pipelines_when_loaded() {
+      let parcel_rc = ParcelRcFile {
+        path: PathBuf::from("/.parcelrc"),
+        contents: serde_json5::from_str(
+          r#"{
+            "bundler": "@parcel/bundler-default",
+            "namers": ["@parcel/namer-default"],
+            "resolvers": ["@parcel/resolver-default"],
+            "transformers": { "*.js": [] }
+          }"#,
+        )
+        .unwrap(),
+      };
+
+      let extended = PartialParcelConfig::try_from(&ParcelRcFile {
+        path: PathBuf::from("/base.parcelrc"),
+        contents: serde_json5::from_str(
+          r#"{ "transformers": { "*.js": ["@parcel/transformer-js"] } }"#,
+        )
+        .unwrap(),
+      })
+      .unwrap();
+
+      let config = PartialParcelConfig::try_from(&parcel_rc)
+        .map(|config| PartialParcelConfig::merge(config, extended))
+        .and_then(ParcelConfig::try_from)
+        .unwrap();
+
+      assert_eq!(
+        config
+          .transformers(Path::new("a.js"), &None::<&str>, false)
+          .map_err(|e| e.to_string()),
+        Err(String::from("The transformers pipeline for *.js is empty"))
+      );
+    }
+
+    #[test]
+    fn returns_copy_transformer_for_unmatched_files() {
+      let config = config().with_default_to_copy(true);
+      let transformers = config
+        .transformers(Path::new("a.wasm"), &None::<&str>, false)
+        .unwrap();
+
+      assert_eq!(
+        transformers,
+        vec![PluginNode::copy_transformer(Rc::new(PathBuf::from("/")))]
+      );
+      assert!(transformers[0].is_copy_transformer());
+      assert_eq!(
+        config
+          .transformers(Path::new("a.js"), &None::<&str>, false)
+          .unwrap(),
+        vec![plugin("@parcel/transformer-js")]
+      );
+    }
+
+    #[test]
+    fn resolves_the_copy_transformer_from_the_loaded_config() {
+      let parcel_rc = ParcelRcFile {
+        path: PathBuf::from("/project/.parcelrc"),
+        contents: serde_json5::from_str(
+          r#"{
+            "bundler": "@parcel/bundler-default",
+            "namers": ["@parcel/namer-default"],
+            "resolvers": ["@parcel/resolver-default"]
+          }"#,
+        )
+        .unwrap(),
+      };
+
+      let config = PartialParcelConfig::try_from(&parcel_rc)
+        .and_then(ParcelConfig::try_from)
+        .unwrap()
+        .with_default_to_copy(true);
+
+      assert_eq!(
+        config
+          .transformers(Path::new("a.wasm"), &None::<&str>, false)
+          .map_err(|e| e.to_string()),
+        Ok(vec![PluginNode::copy_transformer(Rc::new(PathBuf::from(
+          "/project/.parcelrc"
+        )))])
+      );
+    }
+
+    #[test]
+    fn errors_for_explicitly_empty_pipelines_when_copying() {
+      let config = config()
+        .with_transformer("*.wasm", Vec::new())
+        .with_default_to_copy(true);
+
+      assert_eq!(
+        config
+          .transformers(Path::new("a.wasm"), &None::<&str>, false)
+          .map_err(|e| e.to_string()),
+        Err(String::from(
+          "The transformers pipeline for *.wasm is empty"
+        ))
+      );
+    }
+
+    #[test]
+    fn returns_no_transformers_for_unmatched_files_when_empty_is_allowed() {
+      let config = config().with_default_to_copy(true);
+
+      assert_eq!(
+        config
+          .transformers(Path::new("a.wasm"), &None::<&str>, true)
+          .unwrap(),
+        Vec::new()
+      );
+    }
+  }
+
+  mod load_plugin {
+    use parcel_package_manager::MockPackageManager;
+    use parcel_package_manager::Resolution;
+
+    use super::*;
+
+    trait Transformer {
+      fn name(&self) -> &str;
+    }
+
+    struct RustTransformer;
+
+    impl Transformer for RustTransformer {
+      fn name(&self) -> &str {
+        "rust"
+      }
+    }
+
+    fn registry() -> PluginRegistry<Box<dyn Transformer>> {
+      let mut registry: PluginRegistry<Box<dyn Transformer>> = PluginRegistry::new();
+      registry.register("@parcel/transformer-js", || Box::new(RustTransformer));
+      registry
+    }
+
+    #[test]
+    fn returns_registered_plugins_without_resolving_them() {
+      let config = ParcelConfig::default_config(Path::new("/index"));
+      let node = PluginNode {
+        package_name: String::from("@parcel/transformer-js"),
+        resolve_from: Rc::new(PathBuf::from("/index")),
+      };
+
+      let mut package_manager = MockPackageManager::new();
+      package_manager.expect_resolve().never();
+
+      let plugin = config
+        .load_plugin(&node, &registry(), &package_manager)
+        .map_err(|e| e.to_string());
+
+      assert!(matches!(plugin, Ok(LoadedPlugin::InProcess(p)) if p.name() == "rust"));
+    }
+
+    #[test]
+    fn resolves_unregistered_plugins() {
+      let config = ParcelConfig::default_config(Path::new("/index"));
+      let node = config.bundler.clone();
+
+      let mut package_manager = MockPackageManager::new();
+      package_manager.expect_resolve().times(1).returning(|_, _| {
+        Ok(Resolution {
+          resolved: PathBuf::from("/node_modules/@parcel/bundler-default/lib/index.js"),
+        })
+      });
+
+      let plugin = config
+        .load_plugin(&node, &registry(), &package_manager)
+        .map_err(|e| e.to_string());
+
+      assert!(matches!(
+        plugin,
+        Ok(LoadedPlugin::Resolved(path))
+          if path == Path::new("/node_modules/@parcel/bundler-default/lib/index.js")
+      ));
+    }
+  }
+
+  mod check_plugin_versions {
+    use parcel_filesystem::in_memory_file_system::InMemoryFileSystem;
+    use parcel_package_manager::MockPackageManager;
+    use parcel_package_manager::Resolution;
+
+    use super::*;
+
+    fn config() -> ParcelConfig {
+      config_builder()
+        .transformers("*.js", vec![plugin("./local-transformer")])
+        .build()
+        .unwrap()
+    }
+
+    fn package_manager() -> MockPackageManager {
+      let mut package_manager = MockPackageManager::new();
+      package_manager
+        .expect_resolve()
+        .returning(|specifier, _from| {
+          Ok(Resolution {
+            resolved: PathBuf::from("/node_modules")
+              .join(specifier)
+              .join("lib/index.js"),
+          })
+        });
+
+      package_manager
+    }
+
+    fn fs(bundler_engines: &str) -> InMemoryFileSystem {
+      let mut fs = InMemoryFileSystem::default();
+      fs.write_file(
+        "/node_modules/@parcel/bundler-default/package.json",
+        format!(r#"{{ "name": "@parcel/bundler-default", {bundler_engines} }}"#),
+      );
+      fs.write_file(
+        "/node_modules/@parcel/namer-default/package.json",
+        r#"{ "name": "@parcel/namer-default", "engines": { "parcel": "^2.0.0" } }"#,
+      );
+
+      fs
+    }
+
+    #[test]
+    fn returns_no_warnings_for_compatible_plugins() {
+      let fs = fs(r#""engines": { "parcel": "^2.0.0" }"#);
+
+      assert_eq!(
+        config().check_plugin_versions(&fs, &package_manager(), "2.12.0"),
+        Vec::new()
+      );
+    }
+
+    #[test]
+    fn skips_plugins_without_engines() {
+      let fs = fs(r#""main": "lib/index.js""#);
+
+      assert_eq!(
+        config().check_plugin_versions(&fs, &package_manager(), "2.12.0"),
+        Vec::new()
+      );
+    }
+
+    #[test]
+    fn returns_warnings_for_incompatible_plugins() {
+      let fs = fs(r#""engines": { "parcel": "^3.0.0" }"#);
+
+      assert_eq!(
+        config().check_plugin_versions(&fs, &package_manager(), "2.12.0"),
+        vec![ConfigWarning::IncompatiblePlugin {
+          package_name: String::from("@parcel/bundler-default"),
+          range: String::from("^3.0.0"),
+          version: String::from("2.12.0"),
+        }]
+      );
+    }
+  }
+
+  mod packager {
+    use super::*;
+
+    #[test]
+    fn returns_first_matching_packager_for_each_lookup() {
+      let config = config_builder()
+        .packager("*.{js,mjs}", plugin("@parcel/packager-js"))
+        .packager("*", plugin("@parcel/packager-raw"))
+        .build()
+        .unwrap();
+
+      assert_eq!(
+        config.packager(Path::new("dist/a.mjs")).ok(),
+        Some(&plugin("@parcel/packager-js"))
+      );
+      assert_eq!(
+        config.packager(Path::new("dist/a.js")).ok(),
+        Some(&plugin("@parcel/packager-js"))
+      );
+      assert_eq!(
+        config.packager(Path::new("dist/a.css")).ok(),
+        Some(&plugin("@parcel/packager-raw"))
+      );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn returns_packager_for_paths_with_backslash_separators() {
+      let config = config_builder()
+        .packager("dist/*.js", plugin("@parcel/packager-js"))
+        .build()
+        .unwrap();
+
+      assert_eq!(
+        config.packager(Path::new("dist\\a.js")).ok(),
+        Some(&plugin("@parcel/packager-js"))
+      );
+      assert_eq!(config.packager(Path::new("lib\\a.js")).ok(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn treats_backslashes_as_file_name_characters() {
+      let config = config_builder()
+        .packager("dist/*.js", plugin("@parcel/packager-js"))
+        .build()
+        .unwrap();
+
+      assert_eq!(config.packager(Path::new("dist\\a.js")).ok(), None);
+    }
+  }
+
+  mod with_plugins {
+    use super::*;
+
+    fn config() -> ParcelConfig {
+      config_builder()
+        .transformers("*.js", vec![plugin("@parcel/transformer-js")])
+        .transformers("*.ts", vec![plugin("@parcel/transformer-js")])
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn replaces_the_bundler() {
+      let config = config().with_bundler(plugin("@scope/parcel-bundler"));
+
+      assert_eq!(
+        config.bundler::<&str>().unwrap(),
+        &plugin("@scope/parcel-bundler")
+      );
+    }
+
+    #[test]
+    fn replaces_transformers_of_a_pattern() {
+      let config =
+        config().with_transformer("*.ts", vec![plugin("@parcel/transformer-typescript-tsc")]);
+
+      assert_eq!(
+        config
+          .transformers(Path::new("a.ts"), &None::<&str>, false)
+          .unwrap(),
+        vec![plugin("@parcel/transformer-typescript-tsc")]
+      );
+      assert_eq!(
+        config
+          .transformers(Path::new("a.js"), &None::<&str>, false)
+          .unwrap(),
+        vec![plugin("@parcel/transformer-js")]
+      );
+    }
+
+    #[test]
+    fn adds_transformers_for_new_patterns() {
+      let config = config().with_transformer("*.css", vec![plugin("@parcel/transformer-css")]);
+
+      assert_eq!(
+        config
+          .transformers(Path::new("a.css"), &None::<&str>, false)
+          .unwrap(),
+        vec![plugin("@parcel/transformer-css")]
+      );
+      assert!(config.has_transformers(Path::new("a.css"), &None::<&str>));
+    }
+  }
+
+  mod runtimes_for {
+    use super::*;
+
+    struct Environment;
+
+    #[test]
+    fn returns_all_runtimes() {
+      let runtimes = vec![
+        plugin("@parcel/runtime-js"),
+        plugin("@parcel/runtime-browser-hmr"),
+      ];
+
+      let config = config_builder().runtimes(runtimes.clone()).build().unwrap();
+
+      assert_eq!(config.runtimes_for(&Environment), runtimes);
+    }
+  }
+
+  mod resolve_with {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    fn config() -> ParcelConfig {
+      config_builder()
+        .resolvers(vec![
+          plugin("parcel-resolver-glob"),
+          plugin("@parcel/resolver-default"),
+          plugin("parcel-resolver-unused"),
+        ])
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn returns_the_first_resolution_after_deferred_resolvers() {
+      let invoked = RefCell::new(Vec::new());
+
+      let resolution = config().resolve_with("./a.js", Path::new("/src/index.js"), |r, s, from| {
+        invoked.borrow_mut().push(r.package_name.clone());
+
+        match r.package_name.as_str() {
+          "parcel-resolver-glob" => ResolverOutcome::Deferred,
+          _ => ResolverOutcome::Resolved(Resolution {
+            resolved: from.parent().unwrap().join(s),
+          }),
+        }
+      });
+
+      assert_eq!(
+        resolution.map(|r| r.resolved).map_err(|e| e.to_string()),
+        Ok(PathBuf::from("/src/./a.js"))
+      );
+      assert_eq!(
+        invoked.into_inner(),
+        vec!["parcel-resolver-glob", "@parcel/resolver-default"]
+      );
+    }
+
+    #[test]
+    fn errors_when_a_resolver_fails() {
+      let resolution =
+        config().resolve_with("./a.js", Path::new("/src/index.js"), |r, _, _| {
+          match r.package_name.as_str() {
+            "parcel-resolver-glob" => ResolverOutcome::Deferred,
+            _ => ResolverOutcome::Failed("Not found".into()),
+          }
+        });
+
+      assert_eq!(
+        resolution.map(|r| r.resolved).map_err(|e| e.to_string()),
+        Err(String::from(
+          "Failed to resolve ./a.js from /src/index.js with @parcel/resolver-default"
+        ))
+      );
+    }
+
+    #[test]
+    fn errors_when_every_resolver_defers() {
+      let resolution = config().resolve_with("./a.js", Path::new("/src/index.js"), |_, _, _| {
+        ResolverOutcome::Deferred
+      });
+
+      assert_eq!(
+        resolution.map(|r| r.resolved).map_err(|e| e.to_string()),
+        Err(String::from(
+          "No resolver could resolve ./a.js from /src/index.js"
+        ))
+      );
+    }
+  }
+
+  mod has_transformers {
+    use super::*;
+
+    fn config() -> ParcelConfig {
+      config_builder()
+        .transformers("*.js", vec![plugin("@parcel/transformer-js")])
+        .transformers(
+          "types:*.ts",
+          vec![plugin("@parcel/transformer-typescript-types")],
+        )
+        .transformers("*.css", Vec::new())
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn returns_true_for_matched_paths() {
+      let config = config();
+
+      assert!(config.has_transformers(Path::new("a.js"), &None::<&str>));
+      assert!(config.has_transformers(Path::new("/src/a.js"), &None::<&str>));
+      assert!(config.has_transformers(Path::new("a.ts"), &Some("types")));
+    }
+
+    #[test]
+    fn returns_false_for_unmatched_paths() {
+      let config = config();
+
+      assert!(!config.has_transformers(Path::new("a.ts"), &None::<&str>));
+      assert!(!config.has_transformers(Path::new("a.css"), &None::<&str>));
+      assert!(!config.has_transformers(Path::new("a.js"), &Some("url")));
+    }
+  }
+
+  mod resolve_plugins_for {
+    use super::*;
+    use crate::parcel_config_fixtures::default_config;
+
+    #[test]
+    fn returns_plugins_from_each_phase() {
+      let config = default_config(&Rc::new(PathBuf::from("/.parcelrc"))).parcel_config;
+
+      for path in [Path::new("index.js"), Path::new("index.ts")] {
+        let pipeline: &Option<&str> = &None;
+
+        assert_eq!(
+          config
+            .resolve_plugins_for(path, pipeline)
+            .map_err(|e| e.to_string()),
+          Ok(ResolvedPlugins {
+            resolvers: config.resolvers().unwrap().clone(),
+            transformers: config.transformers(path, pipeline, false).unwrap(),
+            validators: config.validators(path).unwrap(),
+            optimizers: config.optimizers(path, pipeline).unwrap(),
+            packager: config.packager(path).ok().cloned(),
+            compressors: config.compressors(path).unwrap(),
+          })
+        );
+      }
+    }
+
+    #[test]
+    fn errors_when_transformers_are_missing() {
+      let config = default_config(&Rc::new(PathBuf::from("/.parcelrc"))).parcel_config;
+      let path = Path::new("index.css");
+
+      assert_eq!(
+        config
+          .resolve_plugins_for(path, &None::<&str>)
+          .map_err(|e| e.to_string()),
+        Err(
+          ConfigError::MissingPlugin {
+            path: PathBuf::from(path),
+            phase: String::from("transformers"),
+            pipeline: None,
+          }
+          .to_string()
+        )
+      );
+    }
+  }
+
+  mod validate {
+    use indexmap::indexmap;
+    use parcel_filesystem::in_memory_file_system::InMemoryFileSystem;
+    use parcel_package_manager::MockPackageManager;
+
+    use super::*;
+    use crate::parcel_config_fixtures::default_config;
+    use crate::parcel_rc_config_loader::LoadConfigOptions;
+    use crate::parcel_rc_config_loader::ParcelRcConfigLoader;
+
+    #[test]
+    fn returns_warning_for_duplicate_extends() {
+      let project_root = PathBuf::from("/project");
+      let fs = InMemoryFileSystem::from_tree(
+        &project_root,
+        r#"
+          .parcelrc: { "extends": ["./base.parcelrc", "./base.parcelrc"] }
+          base.parcelrc: { "bundler": "@parcel/bundler-default", "namers": ["@parcel/namer-default"], "resolvers": ["@parcel/resolver-default"] }
+        "#,
+      );
+
+      let (config, files) = ParcelRcConfigLoader::new(&fs, &MockPackageManager::new())
+        .load(&project_root, LoadConfigOptions::default())
+        .unwrap();
+
+      assert_eq!(
+        files,
+        vec![
+          project_root.join(".parcelrc"),
+          project_root.join("base.parcelrc")
+        ]
+      );
+      assert_eq!(
+        config.validate(),
+        vec![ConfigWarning::DuplicateExtends {
+          index: 1,
+          path: project_root.join(".parcelrc"),
+          specifier: String::from("./base.parcelrc"),
+        }]
+      );
+    }
+
+    #[test]
+    fn returns_no_warnings_for_matching_pipelines() {
+      let mut config = default_config(&Rc::new(PathBuf::from("/.parcelrc"))).parcel_config;
+
+      config.transformers = PipelineMap::new(indexmap! {
+        String::from("bundle-text:*") => vec![plugin("@parcel/transformer-inline-string")],
+      });
+      config.optimizers = PipelineMap::new(indexmap! {
+        String::from("bundle-text:*") => vec![plugin("@parcel/optimizer-css")],
+      });
+
+      assert_eq!(config.validate(), Vec::new());
+    }
+
+    #[test]
+    fn returns_warning_for_orphan_pipelines() {
+      let mut config = default_config(&Rc::new(PathBuf::from("/.parcelrc"))).parcel_config;
+
+      config.transformers = PipelineMap::new(indexmap! {
+        String::from("bundle-text:*") => vec![plugin("@parcel/transformer-inline-string")],
+      });
+      config.optimizers = PipelineMap::new(indexmap! {
+        String::from("bundle-text:*") => vec![plugin("@parcel/optimizer-css")],
+        String::from("bundel-text:*") => vec![plugin("@parcel/optimizer-css")],
+      });
+      config.compressors = PipelineMap::new(indexmap! {
+        String::from("data-url:*") => vec![plugin("@parcel/compressor-gzip")],
+      });
+
+      assert_eq!(
+        config.validate(),
+        vec![
+          ConfigWarning::OrphanPipelines {
+            phase: String::from("optimizers"),
+            pipelines: vec![String::from("bundel-text")],
+          },
+          ConfigWarning::OrphanPipelines {
+            phase: String::from("compressors"),
+            pipelines: vec![String::from("data-url")],
+          }
+        ]
+      );
+    }
+
+    #[test]
+    fn returns_no_warnings_for_satisfied_spreads() {
+      let base = ParcelConfig::builder()
+        .transformers("*.vue", vec![plugin("@parcel/transformer-vue")])
+        .config;
+      let config = config_builder()
+        .transformers("*.vue", vec![plugin("...")])
+        .transformers(
+          "*.js",
+          vec![plugin("..."), plugin("@parcel/transformer-js")],
+        )
+        .config;
+
+      let config = ParcelConfig::try_from(PartialParcelConfig::merge(config, base)).unwrap();
+
+      assert_eq!(config.validate(), Vec::new());
+    }
+
+    #[test]
+    fn returns_warning_for_unsatisfied_spreads() {
+      let config = config_builder()
+        .transformers("*.vue", vec![plugin("...")])
+        .transformers(
+          "*.js",
+          vec![plugin("..."), plugin("@parcel/transformer-js")],
+        )
+        .build()
+        .unwrap();
+
+      assert_eq!(
+        config.validate(),
+        vec![ConfigWarning::UnsatisfiedSpread {
+          phase: String::from("transformers"),
+          pattern: String::from("*.vue"),
+        }]
+      );
+    }
+  }
+
+  mod validate_reserved_pipelines {
+    use indexmap::indexmap;
+
+    use super::*;
+    use crate::parcel_config_fixtures::default_config;
+
+    #[test]
+    fn returns_no_warnings_for_reserved_pipelines_in_allowed_phases() {
+      let mut config = default_config(&Rc::new(PathBuf::from("/.parcelrc"))).parcel_config;
+
+      config.transformers = PipelineMap::new(indexmap! {
+        String::from("bundle-text:*") => vec![plugin("@parcel/transformer-inline-string")],
+        String::from("data-url:*") => vec![plugin("@parcel/transformer-inline-string")],
+        String::from("url:*") => vec![plugin("@parcel/transformer-raw")],
+      });
+      config.optimizers = PipelineMap::new(indexmap! {
+        String::from("data-url:*") => vec![plugin("@parcel/optimizer-data-url")],
+      });
+
+      assert_eq!(config.validate(), Vec::new());
+    }
+
+    #[test]
+    fn returns_warnings_for_reserved_pipelines_in_other_phases() {
+      let mut config = default_config(&Rc::new(PathBuf::from("/.parcelrc"))).parcel_config;
+
+      config.transformers = PipelineMap::new(indexmap! {
+        String::from("node:*") => vec![plugin("@parcel/transformer-js")],
+        String::from("url:*") => vec![plugin("@parcel/transformer-raw")],
+      });
+      config.validators = PipelineMap::new(indexmap! {
+        String::from("url:*.ts") => vec![plugin("@parcel/validator-typescript")],
+      });
+      config.packagers = indexmap! {
+        String::from("data-url:*.js") => plugin("@parcel/packager-js"),
+      };
+
+      assert_eq!(
+        config.validate(),
+        vec![
+          ConfigWarning::ReservedPipeline {
+            phase: String::from("packagers"),
+            pipeline: String::from("data-url"),
+          },
+          ConfigWarning::ReservedPipeline {
+            phase: String::from("transformers"),
+            pipeline: String::from("node"),
+          },
+          ConfigWarning::ReservedPipeline {
+            phase: String::from("validators"),
+            pipeline: String::from("url"),
+          },
+        ]
+      );
+    }
+  }
+
+  mod dead_patterns {
+    use super::*;
+
+    #[test]
+    fn returns_patterns_without_matching_files() {
+      let config = config_builder()
+        .packager("*.js", plugin("@parcel/packager-js"))
+        .packager("*.coffee", plugin("@parcel/packager-js"))
+        .transformers("*.js", vec![plugin("@parcel/transformer-js")])
+        .transformers(
+          "legacy/*.coffee",
+          vec![plugin("@parcel/transformer-coffeescript")],
+        )
+        .validators("types:*.js", vec![plugin("@parcel/validator-eslint")])
+        .build()
+        .unwrap();
+
+      let files = vec![PathBuf::from("src/index.js"), PathBuf::from("src/util.js")];
+
+      assert_eq!(
+        config.dead_patterns(&files),
+        vec![
+          (PluginCategory::Packager, String::from("*.coffee")),
+          (PluginCategory::Transformer, String::from("legacy/*.coffee")),
+        ]
+      );
+    }
+  }
+
+  mod structural_eq {
+    use super::*;
+    use crate::parcel_config_fixtures::default_extended_config;
+
+    #[test]
+    fn returns_true_for_configs_from_different_roots() {
+      let config = default_extended_config(&PathBuf::from("/a/project")).parcel_config;
+      let other = default_extended_config(&PathBuf::from("/b/project")).parcel_config;
+
+      assert_ne!(config, other);
+      assert!(config.structural_eq(&other, Path::new("/a/project"), Path::new("/b/project")));
+    }
+
+    #[test]
+    fn returns_false_for_different_plugins() {
+      let config = default_extended_config(&PathBuf::from("/a/project")).parcel_config;
+      let mut other = default_extended_config(&PathBuf::from("/b/project")).parcel_config;
+
+      other.reporters.push(plugin("@parcel/reporter-cli"));
+
+      assert!(!config.structural_eq(&other, Path::new("/a/project"), Path::new("/b/project")));
+    }
+
+    #[test]
+    fn returns_false_for_plugins_resolved_from_different_places() {
+      let config = default_extended_config(&PathBuf::from("/a/project")).parcel_config;
+      let other = default_extended_config(&PathBuf::from("/b/project")).parcel_config;
+
+      assert!(!config.structural_eq(&other, Path::new("/a"), Path::new("/b/project")));
+    }
+  }
+
+  mod diff {
+    use indexmap::indexmap;
+
+    use super::*;
+    use crate::parcel_config_fixtures::default_config;
+
+    #[test]
+    fn returns_empty_diff_for_equal_configs() {
+      let resolve_from = Rc::new(PathBuf::from("/.parcelrc"));
+      let config = default_config(&resolve_from).parcel_config;
+      let diff = config.diff(&default_config(&resolve_from).parcel_config);
+
+      assert!(diff.is_empty());
+      assert_eq!(diff, ConfigDiff::default());
+    }
+
+    #[test]
+    fn returns_empty_diff_for_reordered_brace_sets() {
+      let config = ParcelConfig {
+        transformers: PipelineMap::new(indexmap! {
+          String::from("*.{js,ts}") => vec![plugin("@parcel/transformer-js")],
+        }),
+        ..default_config(&Rc::new(PathBuf::from("/.parcelrc"))).parcel_config
+      };
+
+      let other = ParcelConfig {
+        transformers: PipelineMap::new(indexmap! {
+          String::from("*.{ts,js}") => vec![plugin("@parcel/transformer-js")],
+        }),
+        ..default_config(&Rc::new(PathBuf::from("/.parcelrc"))).parcel_config
+      };
+
+      assert_eq!(config, other);
+      assert_eq!(config.diff(&other), ConfigDiff::default());
+    }
+
+    #[test]
+    fn returns_changed_reporters() {
+      let resolve_from = Rc::new(PathBuf::from("/.parcelrc"));
+      let config = default_config(&resolve_from).parcel_config;
+      let mut other = default_config(&resolve_from).parcel_config;
+
+      other.reporters.push(plugin("@parcel/reporter-cli"));
+
+      let diff = config.diff(&other);
+
+      assert_eq!(
+        diff,
+        ConfigDiff {
+          reporters: true,
+          ..ConfigDiff::default()
+        }
+      );
+      assert!(!diff.is_empty());
+      assert!(!diff.requires_rebuild());
+    }
+
+    #[test]
+    fn returns_changed_transformer_patterns() {
+      let resolve_from = Rc::new(PathBuf::from("/.parcelrc"));
+      let config = default_config(&resolve_from).parcel_config;
+      let mut other = default_config(&resolve_from).parcel_config;
+
+      other.transformers = PipelineMap::new(indexmap! {
+        String::from("*.{js,mjs,jsm,jsx,es6,cjs,ts,tsx}") => vec![plugin("@parcel/transformer-babel")],
+        String::from("*.toml") => vec![plugin("@parcel/transformer-toml")],
+      });
+
+      let diff = config.diff(&other);
+
+      assert_eq!(
+        diff,
+        ConfigDiff {
+          transformers: vec![
+            String::from("*.{js,mjs,jsm,jsx,es6,cjs,ts,tsx}"),
+            String::from("*.toml")
+          ],
+          ..ConfigDiff::default()
+        }
+      );
+      assert!(diff.requires_rebuild());
+    }
   }
 }
@@ -1,10 +1,14 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::rc::Rc;
 
 use indexmap::indexmap;
 use indexmap::IndexMap;
 
+use super::parcel_config::PackagerMatchers;
 use super::parcel_config::ParcelConfig;
+use super::parcel_config::ParcelConfigBuilder;
+use super::parcel_config::PluginPathCache;
 use super::pipeline::PipelineMap;
 use crate::parcel_config::PluginNode;
 
@@ -49,6 +53,19 @@ pub fn fallback_config(project_root: &PathBuf) -> (String, ConfigFixture) {
   )
 }
 
+/// Returns a builder holding the plugins that every config requires, resolved from the root
+pub fn config_builder() -> ParcelConfigBuilder {
+  let plugin = |package_name: &str| PluginNode {
+    package_name: String::from(package_name),
+    resolve_from: Rc::new(PathBuf::from("/")),
+  };
+
+  ParcelConfig::builder()
+    .bundler(plugin("@parcel/bundler-default"))
+    .namers(vec![plugin("@parcel/namer-default")])
+    .resolvers(vec![plugin("@parcel/resolver-default")])
+}
+
 pub fn default_config(resolve_from: &Rc<PathBuf>) -> ConfigFixture {
   ConfigFixture {
     parcel_config: ParcelConfig {
@@ -62,6 +79,9 @@ pub fn default_config(resolve_from: &Rc<PathBuf>) -> ConfigFixture {
           resolve_from: Rc::clone(&resolve_from),
         })
       }),
+      default_to_copy: false,
+      extends_depths: HashMap::from([(PathBuf::from(resolve_from.as_path()), 0)]),
+      extends_warnings: Vec::new(),
       namers: vec![PluginNode {
         package_name: String::from("@parcel/namer-default"),
         resolve_from: Rc::clone(&resolve_from),
@@ -78,6 +98,8 @@ pub fn default_config(resolve_from: &Rc<PathBuf>) -> ConfigFixture {
           resolve_from: Rc::clone(&resolve_from),
         }
       },
+      packager_matchers: PackagerMatchers::default(),
+      plugin_paths: PluginPathCache::default(),
       reporters: vec![PluginNode {
         package_name: String::from("@parcel/reporter-dev-server"),
         resolve_from: Rc::clone(&resolve_from),
@@ -96,6 +118,7 @@ pub fn default_config(resolve_from: &Rc<PathBuf>) -> ConfigFixture {
           resolve_from: Rc::clone(&resolve_from),
         })
       }),
+      unsatisfied_spreads: Vec::new(),
       validators: PipelineMap::new(IndexMap::new()),
     },
     parcel_rc: String::from(
@@ -152,6 +175,12 @@ fn extended_config_from(
           resolve_from: Rc::clone(&extended_resolve_from),
         })
       }),
+      default_to_copy: false,
+      extends_depths: HashMap::from([
+        (PathBuf::from(base_resolve_from.as_path()), 0),
+        (PathBuf::from(extended_resolve_from.as_path()), 1),
+      ]),
+      extends_warnings: Vec::new(),
       namers: vec![PluginNode {
         package_name: String::from("@parcel/namer-default"),
         resolve_from: Rc::clone(&extended_resolve_from),
@@ -168,13 +197,15 @@ fn extended_config_from(
           resolve_from: Rc::clone(&extended_resolve_from),
         }
       },
+      packager_matchers: PackagerMatchers::default(),
+      plugin_paths: PluginPathCache::default(),
       reporters: vec![
         PluginNode {
           package_name: String::from("@parcel/reporter-dev-server"),
           resolve_from: Rc::clone(&extended_resolve_from),
         },
         PluginNode {
-          package_name: String::from("@scope/parcel-metrics-reporter"),
+          package_name: String::from("@scope/parcel-reporter-metrics"),
           resolve_from: Rc::clone(&base_resolve_from),
         },
       ],
@@ -196,6 +227,7 @@ fn extended_config_from(
           resolve_from: Rc::clone(&base_resolve_from),
         }),
       }),
+      unsatisfied_spreads: Vec::new(),
       validators: PipelineMap::new(IndexMap::new()),
     },
     base_config: PartialConfigFixture {
@@ -204,7 +236,7 @@ fn extended_config_from(
         r#"
           {
             "extends": "@parcel/config-default",
-            "reporters": ["...", "@scope/parcel-metrics-reporter"],
+            "reporters": ["...", "@scope/parcel-reporter-metrics"],
             "transformers": {
               "*.{ts,tsx}": [
                 "@scope/parcel-transformer-ts",
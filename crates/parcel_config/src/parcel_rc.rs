@@ -1,8 +1,14 @@
+use std::path::Path;
 use std::path::PathBuf;
 
 use indexmap::IndexMap;
 use serde::Deserialize;
 
+use super::config_error::ConfigError;
+use super::config_error::ConfigWarning;
+use super::source_location::PluginKey;
+use super::source_location::PluginLocations;
+
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 pub enum Extends {
@@ -10,20 +16,187 @@ pub enum Extends {
   Many(Vec<String>),
 }
 
+impl Extends {
+  /// Returns the configs to extend, in order and without duplicates
+  ///
+  /// Each duplicate that is dropped is returned as a warning with its index. Errors with the
+  /// index of the first empty specifier, since it cannot refer to any config.
+  ///
+  pub fn specifiers(&self, path: &Path) -> Result<(Vec<String>, Vec<ConfigWarning>), ConfigError> {
+    let extends = match self {
+      Extends::One(extend) => std::slice::from_ref(extend),
+      Extends::Many(extends) => extends.as_slice(),
+    };
+
+    let mut specifiers: Vec<String> = Vec::new();
+    let mut warnings = Vec::new();
+    for (index, extend) in extends.iter().enumerate() {
+      if extend.trim().is_empty() {
+        return Err(ConfigError::EmptyExtends {
+          index,
+          path: PathBuf::from(path),
+        });
+      }
+
+      if specifiers.contains(extend) {
+        warnings.push(ConfigWarning::DuplicateExtends {
+          index,
+          path: PathBuf::from(path),
+          specifier: String::from(extend),
+        });
+      } else {
+        specifiers.push(String::from(extend));
+      }
+    }
+
+    Ok((specifiers, warnings))
+  }
+}
+
+/// A plugin listed in a .parcelrc pipeline
+///
+/// Plugins are listed by package name, but can be disabled without removing them from the file
+/// by replacing the name with `false`, or with `{ "plugin": "name", "disabled": true }`. A `true`
+/// entry does not name a plugin, so it is rejected when the config is loaded.
+///
+/// A list whose plugins are all disabled is treated as if it was not written, so the plugins of
+/// extended configs are used in its place. This applies to lists such as "reporters" as well as
+/// to the pipelines of patterns, where it differs from an empty pipeline that runs no plugins.
+///
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum PluginEntry {
+  Name(String),
+  Bool(bool),
+  Object {
+    plugin: String,
+    #[serde(default)]
+    disabled: bool,
+  },
+}
+
+impl PluginEntry {
+  /// Returns the package name of the plugin, or None when it is disabled
+  pub fn package_name(&self) -> Option<&str> {
+    match self {
+      PluginEntry::Name(name) => Some(name),
+      PluginEntry::Bool(_) => None,
+      PluginEntry::Object { plugin, disabled } => (!disabled).then_some(plugin.as_str()),
+    }
+  }
+}
+
 /// Deserialized .parcel_rc config
 #[derive(Debug, Deserialize)]
 pub struct ParcelRc {
   pub extends: Option<Extends>,
   pub bundler: Option<String>,
-  pub compressors: Option<IndexMap<String, Vec<String>>>,
-  pub namers: Option<Vec<String>>,
-  pub optimizers: Option<IndexMap<String, Vec<String>>>,
+  pub compressors: Option<IndexMap<String, Vec<PluginEntry>>>,
+  pub namers: Option<Vec<PluginEntry>>,
+  pub optimizers: Option<IndexMap<String, Vec<PluginEntry>>>,
   pub packagers: Option<IndexMap<String, String>>,
-  pub reporters: Option<Vec<String>>,
-  pub resolvers: Option<Vec<String>>,
-  pub runtimes: Option<Vec<String>>,
-  pub transformers: Option<IndexMap<String, Vec<String>>>,
-  pub validators: Option<IndexMap<String, Vec<String>>>,
+  pub reporters: Option<Vec<PluginEntry>>,
+  pub resolvers: Option<Vec<PluginEntry>>,
+  pub runtimes: Option<Vec<PluginEntry>>,
+  pub transformers: Option<IndexMap<String, Vec<PluginEntry>>>,
+  pub validators: Option<IndexMap<String, Vec<PluginEntry>>>,
+}
+
+fn plugin_key(phase: &str, pattern: Option<&String>, index: usize) -> PluginKey {
+  PluginKey {
+    phase: String::from(phase),
+    pattern: pattern.cloned(),
+    index,
+  }
+}
+
+impl ParcelRc {
+  /// Lists the entries of the phases that take a list of plugins, with where they are listed
+  fn entries(&self) -> Vec<(PluginKey, &PluginEntry)> {
+    let mut entries = Vec::new();
+    for (phase, list) in [
+      ("namers", &self.namers),
+      ("reporters", &self.reporters),
+      ("resolvers", &self.resolvers),
+      ("runtimes", &self.runtimes),
+    ] {
+      for (index, entry) in list.iter().flatten().enumerate() {
+        entries.push((plugin_key(phase, None, index), entry));
+      }
+    }
+
+    for (phase, map) in [
+      ("compressors", &self.compressors),
+      ("optimizers", &self.optimizers),
+      ("transformers", &self.transformers),
+      ("validators", &self.validators),
+    ] {
+      for (pattern, list) in map.iter().flatten() {
+        for (index, entry) in list.iter().enumerate() {
+          entries.push((plugin_key(phase, Some(pattern), index), entry));
+        }
+      }
+    }
+
+    entries
+  }
+
+  /// Lists the names of the enabled plugins, with where they are listed
+  fn plugins(&self) -> Vec<(PluginKey, &str)> {
+    let mut plugins = Vec::new();
+    if let Some(bundler) = &self.bundler {
+      plugins.push((plugin_key("bundler", None, 0), bundler.as_str()));
+    }
+
+    for (key, entry) in self.entries() {
+      if let Some(name) = entry.package_name() {
+        plugins.push((key, name));
+      }
+    }
+
+    for (pattern, name) in self.packagers.iter().flatten() {
+      plugins.push((plugin_key("packagers", Some(pattern), 0), name.as_str()));
+    }
+
+    plugins
+  }
+}
+
+/// Checks that a plugin name follows the naming convention of its phase
+///
+/// Returns the convention when it does not. Local plugins, "..." and "null" are not checked.
+///
+fn validate_plugin_name(phase: &str, name: &str) -> Result<(), String> {
+  if name == "..." || name == "null" || name.starts_with('.') || name.starts_with('/') {
+    return Ok(());
+  }
+
+  let plugin_type = phase.strip_suffix('s').unwrap_or(phase);
+  let prefix = format!("parcel-{}-", plugin_type);
+
+  if name.starts_with("@parcel/") {
+    if !name.starts_with(&format!("@parcel/{}-", plugin_type)) {
+      return Err(format!(
+        "Official parcel {0} packages must be named according to \"@parcel/{0}-{{name}}\"",
+        plugin_type
+      ));
+    }
+  } else if let Some(scoped) = name.strip_prefix('@') {
+    let (scope, package) = scoped.split_once('/').unwrap_or((scoped, ""));
+    if !package.starts_with(&prefix) && package != format!("parcel-{}", plugin_type) {
+      return Err(format!(
+        "Scoped parcel {0} packages must be named according to \"@{1}/parcel-{0}[-{{name}}]\"",
+        plugin_type, scope
+      ));
+    }
+  } else if !name.starts_with(&prefix) {
+    return Err(format!(
+      "Parcel {0} packages must be named according to \"parcel-{0}-{{name}}\"",
+      plugin_type
+    ));
+  }
+
+  Ok(())
 }
 
 /// Represents the .parcel_rc config file
@@ -32,3 +205,210 @@ pub struct ParcelRcFile {
   pub path: PathBuf,
   pub contents: ParcelRc,
 }
+
+impl ParcelRcFile {
+  /// Checks that plugins are only disabled with `false`, since `true` does not name a plugin
+  pub fn validate_plugin_entries(&self) -> Result<(), ConfigError> {
+    for (key, entry) in self.contents.entries() {
+      if let PluginEntry::Bool(true) = entry {
+        return Err(ConfigError::InvalidPluginEntry {
+          index: key.index,
+          path: self.path.clone(),
+          pattern: key.pattern,
+          phase: key.phase,
+        });
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Checks that plugin names follow the naming convention of their phase
+  ///
+  /// The error for the first invalid name points at where it is listed in source, which is the
+  /// text of the file before any `${VAR}` references were interpolated.
+  ///
+  pub fn validate_plugin_names(&self, source: &str) -> Result<(), ConfigError> {
+    for (key, name) in self.contents.plugins() {
+      if let Err(message) = validate_plugin_name(&key.phase, name) {
+        let locations = PluginLocations::from_source(&self.path, source);
+
+        return Err(ConfigError::InvalidPluginName {
+          location: locations.get(&key).cloned(),
+          message,
+          name: String::from(name),
+          path: self.path.clone(),
+        });
+      }
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod specifiers {
+    use super::*;
+
+    #[test]
+    fn returns_specifiers_in_order() {
+      let path = Path::new("/.parcelrc");
+
+      assert_eq!(
+        Extends::One(String::from("@parcel/config-default"))
+          .specifiers(path)
+          .map_err(|e| e.to_string()),
+        Ok((vec![String::from("@parcel/config-default")], Vec::new()))
+      );
+
+      assert_eq!(
+        Extends::Many(vec![
+          String::from("./base.parcelrc"),
+          String::from("@scope/config")
+        ])
+        .specifiers(path)
+        .map_err(|e| e.to_string()),
+        Ok((
+          vec![
+            String::from("./base.parcelrc"),
+            String::from("@scope/config")
+          ],
+          Vec::new()
+        ))
+      );
+    }
+
+    #[test]
+    fn removes_duplicate_specifiers_with_a_warning() {
+      assert_eq!(
+        Extends::Many(vec![
+          String::from("./base.parcelrc"),
+          String::from("@scope/config"),
+          String::from("./base.parcelrc"),
+          String::from("@scope/config"),
+        ])
+        .specifiers(Path::new("/.parcelrc"))
+        .map_err(|e| e.to_string()),
+        Ok((
+          vec![
+            String::from("./base.parcelrc"),
+            String::from("@scope/config")
+          ],
+          vec![
+            ConfigWarning::DuplicateExtends {
+              index: 2,
+              path: PathBuf::from("/.parcelrc"),
+              specifier: String::from("./base.parcelrc"),
+            },
+            ConfigWarning::DuplicateExtends {
+              index: 3,
+              path: PathBuf::from("/.parcelrc"),
+              specifier: String::from("@scope/config"),
+            },
+          ]
+        ))
+      );
+    }
+
+    #[test]
+    fn errors_on_empty_specifiers() {
+      let path = Path::new("/.parcelrc");
+
+      assert_eq!(
+        Extends::One(String::new())
+          .specifiers(path)
+          .map_err(|e| e.to_string()),
+        Err(
+          ConfigError::EmptyExtends {
+            index: 0,
+            path: PathBuf::from(path),
+          }
+          .to_string()
+        )
+      );
+
+      assert_eq!(
+        Extends::Many(vec![String::from("./base.parcelrc"), String::from(" ")])
+          .specifiers(path)
+          .map_err(|e| e.to_string()),
+        Err(
+          ConfigError::EmptyExtends {
+            index: 1,
+            path: PathBuf::from(path),
+          }
+          .to_string()
+        )
+      );
+    }
+  }
+
+  mod validate_plugin_names {
+    use super::*;
+
+    fn validate(source: &str) -> Result<(), String> {
+      ParcelRcFile {
+        path: PathBuf::from("/.parcelrc"),
+        contents: serde_json5::from_str(source).unwrap(),
+      }
+      .validate_plugin_names(source)
+      .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn accepts_conventional_names() {
+      assert_eq!(
+        validate(
+          r#"{
+            "bundler": "@parcel/bundler-default",
+            "transformers": {
+              "*.js": ["parcel-transformer-a", "@scope/parcel-transformer", "..."],
+              "*.ts": ["./local-transformer.js"]
+            },
+            "packagers": { "*.css": "null" },
+            "reporters": ["@scope/parcel-reporter-b"]
+          }"#
+        ),
+        Ok(())
+      );
+    }
+
+    #[test]
+    fn errors_with_the_location_of_invalid_names() {
+      let source = r#"{
+  "bundler": "@parcel/bundler-default",
+  "transformers": {
+    "*.js": [
+      "@parcel/transformer-js",
+      "@parcel/optimizer-terser"
+    ]
+  }
+}"#;
+
+      assert_eq!(
+        validate(source),
+        Err(String::from(
+          "Invalid plugin name @parcel/optimizer-terser at /.parcelrc:6:7: Official parcel transformer packages must be named according to \"@parcel/transformer-{name}\""
+        ))
+      );
+    }
+
+    #[test]
+    fn errors_on_invalid_scoped_and_unscoped_names() {
+      assert_eq!(
+        validate(r#"{ "reporters": ["@scope/reporter-cli"] }"#),
+        Err(String::from(
+          "Invalid plugin name @scope/reporter-cli at /.parcelrc:1:17: Scoped parcel reporter packages must be named according to \"@scope/parcel-reporter[-{name}]\""
+        ))
+      );
+      assert_eq!(
+        validate(r#"{ "namers": ["namer-default"] }"#),
+        Err(String::from(
+          "Invalid plugin name namer-default at /.parcelrc:1:14: Parcel namer packages must be named according to \"parcel-namer-{name}\""
+        ))
+      );
+    }
+  }
+}
@@ -1,50 +1,317 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
 
+use indexmap::IndexMap;
 use parcel_filesystem::search::find_ancestor_file;
+use parcel_filesystem::watch::FileEvent;
 use parcel_filesystem::FileSystem;
 use parcel_package_manager::PackageManager;
 use pathdiff::diff_paths;
 
 use super::config_error::ConfigError;
+#[cfg(feature = "http-extends")]
+use super::config_fetcher::is_url;
+#[cfg(feature = "http-extends")]
+use super::config_fetcher::ConfigFetcher;
+use super::merge_trace::MergeTrace;
 use super::parcel_config::ParcelConfig;
 use super::parcel_config::PluginNode;
-use super::parcel_rc::Extends;
+use super::parcel_rc::ParcelRc;
 use super::parcel_rc::ParcelRcFile;
 use super::partial_parcel_config::PartialParcelConfig;
 
 #[derive(Default)]
 pub struct LoadConfigOptions<'a> {
-  /// A list of additional reporter plugins that will be appended to the reporters config
+  /// A list of additional reporter plugins that will be prepended to the reporters config
   pub additional_reporters: Vec<PluginNode>,
   /// A file path or package specifier that will be used to load the config from
   pub config: Option<&'a str>,
+  /// Variables used to interpolate `${VAR}` references in .parcelrc files
+  pub env: HashMap<String, String>,
   /// A file path or package specifier that will be used to load the config from when no other
   /// .parcelrc can be found
   pub fallback_config: Option<&'a str>,
 }
 
+/// Replaces `${VAR}` references within the strings of the .parcelrc contents with values from env
+///
+/// Values are escaped for the string they are placed in, so that a value such as `C:\configs` is
+/// read back as written. References outside of strings, including in comments, are left as is.
+///
+/// A `$` that does not begin a reference is left as is, and `$$` can be used to escape a literal
+/// `$` so that `$${VAR}` produces `${VAR}`.
+///
+fn interpolate_env(
+  path: &Path,
+  contents: &str,
+  env: &HashMap<String, String>,
+) -> Result<String, ConfigError> {
+  let mut interpolated = String::with_capacity(contents.len());
+  let mut rest = contents;
+
+  while let Some(index) = rest.find(['"', '\'', '/']) {
+    interpolated.push_str(&rest[..index]);
+    rest = &rest[index..];
+
+    let end = if rest.starts_with("//") {
+      rest.find('\n').unwrap_or(rest.len())
+    } else if let Some(comment) = rest.strip_prefix("/*") {
+      comment.find("*/").map_or(rest.len(), |end| end + 4)
+    } else if rest.starts_with('/') {
+      1
+    } else {
+      let quote = rest.as_bytes()[0];
+      let mut escaped = false;
+      let end = rest
+        .bytes()
+        .enumerate()
+        .skip(1)
+        .find(|(_index, byte)| {
+          let closes = !escaped && *byte == quote;
+          escaped = !escaped && *byte == b'\\';
+          closes
+        })
+        .map_or(rest.len(), |(end, _byte)| end);
+
+      interpolated.push(char::from(quote));
+      interpolate_string(
+        path,
+        &rest[1..end],
+        char::from(quote),
+        env,
+        &mut interpolated,
+      )?;
+      rest = &rest[end..];
+      if !rest.is_empty() {
+        interpolated.push(char::from(quote));
+        rest = &rest[1..];
+      }
+
+      continue;
+    };
+
+    interpolated.push_str(&rest[..end]);
+    rest = &rest[end..];
+  }
+
+  interpolated.push_str(rest);
+
+  Ok(interpolated)
+}
+
+/// Replaces `${VAR}` references within the contents of a string quoted by quote
+fn interpolate_string(
+  path: &Path,
+  contents: &str,
+  quote: char,
+  env: &HashMap<String, String>,
+  interpolated: &mut String,
+) -> Result<(), ConfigError> {
+  let mut rest = contents;
+
+  while let Some(index) = rest.find('$') {
+    interpolated.push_str(&rest[..index]);
+    rest = &rest[index..];
+
+    if rest.starts_with("$$") {
+      interpolated.push('$');
+      rest = &rest[2..];
+      continue;
+    }
+
+    let variable = rest
+      .strip_prefix("${")
+      .and_then(|r| r.find('}').map(|end| &r[..end]));
+
+    match variable {
+      None => {
+        interpolated.push('$');
+        rest = &rest[1..];
+      }
+      Some(name) => {
+        let value = env
+          .get(name)
+          .ok_or_else(|| ConfigError::UndefinedVariable {
+            name: String::from(name),
+            path: PathBuf::from(path),
+          })?;
+
+        for c in value.chars() {
+          match c {
+            '\\' => interpolated.push_str("\\\\"),
+            '\n' => interpolated.push_str("\\n"),
+            '\r' => interpolated.push_str("\\r"),
+            '\t' => interpolated.push_str("\\t"),
+            c if c == quote => {
+              interpolated.push('\\');
+              interpolated.push(c);
+            }
+            c if c.is_control() => interpolated.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => interpolated.push(c),
+          }
+        }
+
+        rest = &rest[name.len() + 3..];
+      }
+    }
+  }
+
+  interpolated.push_str(rest);
+
+  Ok(())
+}
+
+/// Places the additional reporters before the configured ones, keeping only the first of the
+/// reporters that refer to the same plugin
+fn prepend_reporters(additional: Vec<PluginNode>, reporters: Vec<PluginNode>) -> Vec<PluginNode> {
+  let mut result: Vec<PluginNode> = Vec::new();
+  for reporter in additional.into_iter().chain(reporters) {
+    if !result.iter().any(|r| r.is_same_plugin(&reporter)) {
+      result.push(reporter);
+    }
+  }
+
+  result
+}
+
+/// Merges a config with a config it extends, appending how their pipelines were merged to trace
+fn merge_traced(
+  from_config: PartialParcelConfig,
+  extend_config: PartialParcelConfig,
+  trace: &mut MergeTrace,
+) -> PartialParcelConfig {
+  let (config, merge_trace) = PartialParcelConfig::merge_traced(from_config, extend_config);
+  trace.append(merge_trace);
+
+  config
+}
+
+/// Produces the contents of a config file that is not written in JSON5, such as `.parcelrc.js`
+///
+/// Loaders are provided by the host, so that configs can be generated dynamically without Parcel
+/// embedding a JS engine.
+///
+pub type ConfigLoader = dyn Fn(&Path) -> Result<ParcelRc, Box<dyn std::error::Error>>;
+
+/// The number of extends that can be followed from the loaded config by default
+const DEFAULT_MAX_EXTENDS_DEPTH: usize = 10;
+
 /// Loads and validates .parcel_rc config
 pub struct ParcelRcConfigLoader<'a, T, U> {
+  /// Loaders for `.parcelrc.<extension>` files, keyed by extension in the order they were added
+  config_loaders: IndexMap<String, &'a ConfigLoader>,
+  /// Fetches configs that are extended by URL
+  #[cfg(feature = "http-extends")]
+  fetcher: Option<&'a dyn ConfigFetcher>,
+  /// The nearest .parcelrc found when searching from a directory up to a project root
+  found_configs: RefCell<HashMap<(PathBuf, PathBuf), Option<PathBuf>>>,
   fs: &'a T,
+  /// The number of extends that can be followed from the loaded config
+  max_extends_depth: usize,
   package_manager: &'a U,
 }
 
 impl<'a, T: FileSystem, U: PackageManager> ParcelRcConfigLoader<'a, T, U> {
   pub fn new(fs: &'a T, package_manager: &'a U) -> Self {
     ParcelRcConfigLoader {
+      config_loaders: IndexMap::new(),
+      #[cfg(feature = "http-extends")]
+      fetcher: None,
+      found_configs: RefCell::new(HashMap::new()),
       fs,
+      max_extends_depth: DEFAULT_MAX_EXTENDS_DEPTH,
       package_manager,
     }
   }
 
+  /// Limits how many extends can be followed from the loaded config
+  ///
+  /// Chains this deep are almost always a misconfiguration, so exceeding the limit is an error.
+  ///
+  pub fn with_max_extends_depth(mut self, max_extends_depth: usize) -> Self {
+    self.max_extends_depth = max_extends_depth;
+    self
+  }
+
+  /// Loads config files with the extension, such as `js` for `.parcelrc.js`, using loader
+  ///
+  /// These files are searched for after `.parcelrc`, which is always parsed as JSON5. Configs may
+  /// also extend files with the extension.
+  ///
+  pub fn with_config_loader(mut self, extension: &str, loader: &'a ConfigLoader) -> Self {
+    if extension != "parcelrc" {
+      self.config_loaders.insert(String::from(extension), loader);
+    }
+
+    self
+  }
+
+  /// Returns the file names searched for when finding the nearest config
+  fn config_names(&self) -> Vec<String> {
+    let loaded_names = self
+      .config_loaders
+      .keys()
+      .map(|extension| format!(".parcelrc.{}", extension));
+
+    std::iter::once(String::from(".parcelrc"))
+      .chain(loaded_names)
+      .collect()
+  }
+
+  /// Allows configs to extend configs hosted at http(s) URLs, which are fetched with fetcher
+  #[cfg(feature = "http-extends")]
+  pub fn with_fetcher(mut self, fetcher: &'a dyn ConfigFetcher) -> Self {
+    self.fetcher = Some(fetcher);
+    self
+  }
+
+  /// Forgets the .parcelrc files found so far when one is created or deleted
+  ///
+  /// This should be called with the events of a file system watcher, so that later loads find
+  /// the right config.
+  ///
+  pub fn invalidate(&self, event: &FileEvent) {
+    let path = match event {
+      FileEvent::Create(path) | FileEvent::Delete(path) => path,
+      FileEvent::Update(_) => return,
+    };
+
+    let is_config = path
+      .file_name()
+      .and_then(|name| name.to_str())
+      .is_some_and(|name| self.config_names().iter().any(|n| n == name));
+
+    if is_config {
+      self.found_configs.borrow_mut().clear();
+    }
+  }
+
   fn find_config(&self, project_root: &Path, path: &PathBuf) -> Result<PathBuf, ConfigError> {
     let from = path.parent().unwrap_or(path);
+    let key = (PathBuf::from(from), PathBuf::from(project_root));
+
+    let found_config = self.found_configs.borrow().get(&key).cloned();
+    let found_config = found_config.unwrap_or_else(|| {
+      let found_config = find_ancestor_file(self.fs, self.config_names(), from, project_root);
+
+      self
+        .found_configs
+        .borrow_mut()
+        .insert(key, found_config.clone());
+
+      found_config
+    });
 
-    find_ancestor_file(self.fs, vec![String::from(".parcelrc")], from, project_root)
-      .ok_or(ConfigError::MissingParcelRc(PathBuf::from(from)))
+    found_config.ok_or(ConfigError::MissingParcelRc(PathBuf::from(from)))
   }
 
+  /// Returns the path that configs are resolved from
+  ///
+  /// This is the current working directory when it lives within the project root, so that the
+  /// nearest .parcelrc is found, and the project root otherwise.
+  ///
   fn resolve_from(&self, project_root: &PathBuf) -> PathBuf {
     let cwd = self.fs.cwd().unwrap();
     let relative = diff_paths(cwd.clone(), project_root);
@@ -60,54 +327,185 @@ impl<'a, T: FileSystem, U: PackageManager> ParcelRcConfigLoader<'a, T, U> {
     dir.join("index")
   }
 
-  fn load_config(&self, path: PathBuf) -> Result<(PartialParcelConfig, Vec<PathBuf>), ConfigError> {
-    let parcel_rc =
-      self
-        .fs
-        .read_to_string(&path)
-        .map_err(|source| ConfigError::ReadConfigFile {
-          path: path.clone(),
-          source,
-        })?;
+  /// Adds a config to the chain of configs that extend it, erroring when the chain is too deep
+  fn extends_chain(&self, chain: &[PathBuf], path: &Path) -> Result<Vec<PathBuf>, ConfigError> {
+    let mut chain = chain.to_vec();
+    chain.push(PathBuf::from(path));
+
+    // The loaded config starts the chain, so only the configs after it are extends
+    if chain.len() - 1 > self.max_extends_depth {
+      return Err(ConfigError::ExtendsTooDeep {
+        chain,
+        max_depth: self.max_extends_depth,
+      });
+    }
+
+    Ok(chain)
+  }
+
+  fn load_config(
+    &self,
+    path: PathBuf,
+    env: &HashMap<String, String>,
+    chain: &[PathBuf],
+    trace: &mut MergeTrace,
+  ) -> Result<(PartialParcelConfig, Vec<PathBuf>), ConfigError> {
+    let chain = self.extends_chain(chain, &path)?;
+    let config_loader = path
+      .extension()
+      .and_then(|extension| extension.to_str())
+      .and_then(|extension| self.config_loaders.get(extension));
+
+    if let Some(config_loader) = config_loader {
+      let contents = config_loader(&path).map_err(|source| ConfigError::LoadFailure {
+        path: path.clone(),
+        source,
+      })?;
+
+      return self.process_config(&ParcelRcFile { path, contents }, env, &chain, trace);
+    }
 
+    let source = self
+      .fs
+      .read_to_string(&path)
+      .map_err(|source| ConfigError::ReadConfigFile {
+        path: path.clone(),
+        source,
+      })?;
+
+    let interpolated = interpolate_env(&path, &source, env)?;
     let contents =
-      serde_json5::from_str(&parcel_rc).map_err(|source| ConfigError::ParseFailure {
+      serde_json5::from_str(&interpolated).map_err(|source| ConfigError::ParseFailure {
         path: path.clone(),
         source,
       })?;
 
-    self.process_config(&ParcelRcFile { path, contents })
+    // Interpolated values change the length of strings, so plugins are located in the original text
+    let parcel_rc = ParcelRcFile { path, contents };
+    parcel_rc.validate_plugin_names(&source)?;
+
+    self.process_config(&parcel_rc, env, &chain, trace)
+  }
+
+  /// Fetches and processes a config that is extended by URL
+  ///
+  /// There is no package to resolve the plugins of a remote config from, so they are resolved
+  /// from the directory of the config that extends it, through a path named after the URL. This
+  /// keeps the plugins of the remote config apart from those of the config that extends it, such
+  /// as for their extends depth. The URL is not returned as a config file to watch.
+  ///
+  /// Relative extends within a remote config are rejected, as there is no directory on the file
+  /// system to resolve them from.
+  ///
+  #[cfg(feature = "http-extends")]
+  fn load_remote_config(
+    &self,
+    config_path: &PathBuf,
+    url: &str,
+    env: &HashMap<String, String>,
+    chain: &[PathBuf],
+    trace: &mut MergeTrace,
+  ) -> Result<(PartialParcelConfig, Vec<PathBuf>), ConfigError> {
+    let chain = self.extends_chain(chain, Path::new(url))?;
+    let unresolved = |source| ConfigError::UnresolvedExtends {
+      candidates: vec![String::from(url)],
+      from: config_path.clone(),
+      source,
+      specifier: String::from(url),
+    };
+
+    let fetcher = self
+      .fetcher
+      .ok_or_else(|| unresolved("No config fetcher was provided".into()))?;
+
+    let source = fetcher.fetch(url).map_err(unresolved)?;
+    let interpolated = interpolate_env(Path::new(url), &source, env)?;
+    let contents =
+      serde_json5::from_str(&interpolated).map_err(|source| ConfigError::ParseFailure {
+        path: PathBuf::from(url),
+        source,
+      })?;
+
+    let parcel_rc = ParcelRcFile {
+      path: PathBuf::from(url),
+      contents,
+    };
+    parcel_rc.validate_plugin_names(&source)?;
+
+    if let Some(extends) = parcel_rc.contents.extends.as_ref() {
+      let (specifiers, _warnings) = extends.specifiers(&parcel_rc.path)?;
+      if let Some(extend) = specifiers.into_iter().find(|e| e.starts_with(".")) {
+        return Err(ConfigError::UnresolvedExtends {
+          candidates: vec![extend.clone()],
+          from: parcel_rc.path,
+          source: "Relative extends are not supported in configs fetched from a URL".into(),
+          specifier: extend,
+        });
+      }
+    }
+
+    let resolve_from = config_path.parent().unwrap_or(config_path).join(url);
+    let (config, files) = self.process_config(
+      &ParcelRcFile {
+        path: resolve_from.clone(),
+        contents: parcel_rc.contents,
+      },
+      env,
+      &chain,
+      trace,
+    )?;
+
+    let files = files
+      .into_iter()
+      .filter(|file| *file != resolve_from)
+      .collect();
+
+    Ok((config, files))
   }
 
+  /// Resolves an "extends" specifier to the path of the config it refers to
+  ///
+  /// Relative specifiers are resolved from the directory of the config. Package specifiers are
+  /// resolved by the package manager, falling back to a .parcelrc within the package for config
+  /// packages that do not declare a main entry. Either way, the path is canonicalized by the file
+  /// system so that its symlink handling applies.
+  ///
   fn resolve_extends(
     &self,
     config_path: &PathBuf,
     extend: &String,
   ) -> Result<PathBuf, ConfigError> {
     let path = if extend.starts_with(".") {
-      config_path.parent().unwrap_or(config_path).join(extend)
+      let dir = config_path.parent().unwrap_or(config_path);
+      self.fs.resolve(dir, Path::new(extend))
     } else {
-      self
-        .package_manager
-        .resolve(extend, config_path)
-        .map_err(|source| ConfigError::UnresolvedConfig {
-          config_type: String::from("extended config"),
-          from: PathBuf::from(config_path),
-          source: Box::new(source),
-          specifier: String::from(extend),
-        })?
-        .resolved
+      self.resolve_extends_package(config_path, extend)?
     };
 
-    self
-      .fs
-      .canonicalize_base(path.clone())
-      .map_err(|source| ConfigError::UnresolvedConfig {
-        config_type: String::from("extended config"),
-        from: path,
-        source: Box::new(source),
-        specifier: String::from(extend),
-      })
+    // Missing files are left to be reported when the config is read
+    Ok(self.fs.canonicalize_base(&path).unwrap_or(path))
+  }
+
+  fn resolve_extends_package(
+    &self,
+    config_path: &PathBuf,
+    extend: &String,
+  ) -> Result<PathBuf, ConfigError> {
+    let candidates = vec![extend.clone(), format!("{}/.parcelrc", extend)];
+    let mut error = None;
+    for candidate in candidates.iter() {
+      match self.package_manager.resolve(candidate, config_path) {
+        Ok(resolution) => return Ok(resolution.resolved),
+        Err(err) => error = error.or(Some(err)),
+      }
+    }
+
+    Err(ConfigError::UnresolvedExtends {
+      candidates,
+      from: PathBuf::from(config_path),
+      source: Box::new(error.unwrap()),
+      specifier: String::from(extend),
+    })
   }
 
   /// Processes a .parcelrc file by loading and merging "extends" configurations into a single
@@ -120,37 +518,55 @@ impl<'a, T: FileSystem, U: PackageManager> ParcelRcConfigLoader<'a, T, U> {
   fn process_config(
     &self,
     parcel_rc: &ParcelRcFile,
+    env: &HashMap<String, String>,
+    chain: &[PathBuf],
+    trace: &mut MergeTrace,
   ) -> Result<(PartialParcelConfig, Vec<PathBuf>), ConfigError> {
     let mut files = vec![parcel_rc.path.clone()];
-    let extends = parcel_rc.contents.extends.as_ref();
-    let extends = match extends {
+    let extends = match parcel_rc.contents.extends.as_ref() {
       None => Vec::new(),
-      Some(extends) => match extends {
-        Extends::One(ext) => vec![String::from(ext)],
-        Extends::Many(ext) => ext.to_vec(),
-      },
+      Some(extends) => extends.specifiers(&parcel_rc.path)?.0,
     };
 
     if extends.is_empty() {
       return Ok((PartialParcelConfig::try_from(parcel_rc)?, files));
     }
 
+    // Earlier extends take precedence over later ones, so each config is merged as the base of
+    // the configs that follow it
     let mut merged_config: Option<PartialParcelConfig> = None;
     for extend in extends {
-      let extended_file_path = self.resolve_extends(&parcel_rc.path, &extend)?;
-      let (extended_config, mut extended_file_paths) = self.load_config(extended_file_path)?;
+      #[cfg(feature = "http-extends")]
+      let extended = if is_url(&extend) {
+        Some(self.load_remote_config(&parcel_rc.path, &extend, env, chain, trace)?)
+      } else {
+        None
+      };
 
+      #[cfg(not(feature = "http-extends"))]
+      let extended = None;
+
+      let (extended_config, mut extended_file_paths) = match extended {
+        Some(extended) => extended,
+        None => {
+          let extended_file_path = self.resolve_extends(&parcel_rc.path, &extend)?;
+          self.load_config(extended_file_path, env, chain, trace)?
+        }
+      };
+
+      let extended_config = extended_config.into_extended();
       merged_config = match merged_config {
         None => Some(extended_config),
-        Some(config) => Some(PartialParcelConfig::merge(config, extended_config)),
+        Some(config) => Some(merge_traced(config, extended_config, trace)),
       };
 
       files.append(&mut extended_file_paths);
     }
 
-    let config = PartialParcelConfig::merge(
+    let config = merge_traced(
       PartialParcelConfig::try_from(parcel_rc)?,
       merged_config.unwrap(),
+      trace,
     );
 
     Ok((config, files))
@@ -161,12 +577,39 @@ impl<'a, T: FileSystem, U: PackageManager> ParcelRcConfigLoader<'a, T, U> {
   /// By default the nearest .parcelrc ancestor file from the current working directory will be
   /// loaded, unless the config or fallback_config option are specified. In cases where the
   /// current working directory does not live within the project root, the default config will be
-  /// loaded from the project root.
+  /// loaded from the project root. When no .parcelrc exists and no fallback_config is specified, the
+  /// embedded default config is returned without any config files.
   ///
   pub fn load(
     &self,
     project_root: &PathBuf,
     options: LoadConfigOptions<'a>,
+  ) -> Result<(ParcelConfig, Vec<PathBuf>), ConfigError> {
+    self.load_with_trace(project_root, options, &mut MergeTrace::default())
+  }
+
+  /// Finds and loads a .parcelrc file like [`load`](Self::load), also returning how the pipeline
+  /// of each pattern was merged through the configs it extends
+  ///
+  /// This is intended for debugging why a pipeline holds the plugins it does, such as which
+  /// config filled a "..." several extends away.
+  ///
+  pub fn load_traced(
+    &self,
+    project_root: &PathBuf,
+    options: LoadConfigOptions<'a>,
+  ) -> Result<(ParcelConfig, MergeTrace), ConfigError> {
+    let mut trace = MergeTrace::default();
+    let (parcel_config, _files) = self.load_with_trace(project_root, options, &mut trace)?;
+
+    Ok((parcel_config, trace))
+  }
+
+  fn load_with_trace(
+    &self,
+    project_root: &PathBuf,
+    options: LoadConfigOptions<'a>,
+    trace: &mut MergeTrace,
   ) -> Result<(ParcelConfig, Vec<PathBuf>), ConfigError> {
     let resolve_from = self.resolve_from(project_root);
     let mut config_path = match options.config {
@@ -195,14 +638,22 @@ impl<'a, T: FileSystem, U: PackageManager> ParcelRcConfigLoader<'a, T, U> {
             source: Box::new(source),
             specifier: String::from(fallback_config),
           });
+      } else if options.config.is_none() {
+        // Without any .parcelrc to load, the embedded default config is used
+        let mut parcel_config = ParcelConfig::default_config(&resolve_from);
+        parcel_config.reporters =
+          prepend_reporters(options.additional_reporters, parcel_config.reporters);
+
+        return Ok((parcel_config, Vec::new()));
       }
     }
 
     let config_path = config_path?;
-    let (mut parcel_config, files) = self.load_config(config_path)?;
+    let (mut parcel_config, files) = self.load_config(config_path, &options.env, &[], trace)?;
 
-    if options.additional_reporters.len() > 0 {
-      parcel_config.reporters.extend(options.additional_reporters);
+    if !options.additional_reporters.is_empty() {
+      parcel_config.reporters =
+        prepend_reporters(options.additional_reporters, parcel_config.reporters);
     }
 
     let parcel_config = ParcelConfig::try_from(parcel_config)?;
@@ -246,13 +697,14 @@ mod tests {
     fn resolve(&self, specifier: &str, from: &Path) -> Result<Resolution, ResolveError> {
       let path = match "true" {
         _s if specifier.starts_with(".") => from.join(specifier),
-        _s if specifier.starts_with("@") => self
-          .fs
-          .cwd()
-          .unwrap()
-          .join("node_modules")
-          .join(specifier)
-          .join("index.json"),
+        _s if specifier.starts_with("@") => {
+          let path = self.fs.cwd().unwrap().join("node_modules").join(specifier);
+          if self.fs.is_file(&path) {
+            path
+          } else {
+            path.join("index.json")
+          }
+        }
         _ => PathBuf::from("Not found"),
       };
 
@@ -300,17 +752,20 @@ mod tests {
     use crate::parcel_config_fixtures::default_extended_config;
 
     #[test]
-    fn errors_on_missing_parcelrc_file() {
+    fn returns_embedded_default_config_when_parcelrc_is_missing() {
       let fs = InMemoryFileSystem::default();
       let project_root = fs.cwd().unwrap();
 
-      let err = ParcelRcConfigLoader::new(&fs, &MockPackageManager::new())
+      let parcel_config = ParcelRcConfigLoader::new(&fs, &MockPackageManager::new())
         .load(&project_root, LoadConfigOptions::default())
         .map_err(|e| e.to_string());
 
       assert_eq!(
-        err,
-        Err(ConfigError::MissingParcelRc(project_root).to_string())
+        parcel_config,
+        Ok((
+          ParcelConfig::default_config(&project_root.join("index")),
+          Vec::new()
+        ))
       );
     }
 
@@ -333,8 +788,11 @@ mod tests {
       assert_eq!(
         err,
         Err(
-          ConfigError::UnresolvedConfig {
-            config_type: String::from("extended config"),
+          ConfigError::UnresolvedExtends {
+            candidates: vec![
+              String::from("@parcel/config-default"),
+              String::from("@parcel/config-default/.parcelrc")
+            ],
             from: config.base_config.path,
             specifier: String::from("@parcel/config-default"),
             source: Box::new(ResolveError::NotFound(String::from(""), String::from(""))),
@@ -444,6 +902,7 @@ mod tests {
             additional_reporters: Vec::new(),
             config: Some("@scope/config"),
             fallback_config: None,
+            ..LoadConfigOptions::default()
           },
         )
         .map_err(|e| e.to_string());
@@ -481,6 +940,7 @@ mod tests {
             additional_reporters: Vec::new(),
             config: Some(&specifier),
             fallback_config: None,
+            ..LoadConfigOptions::default()
           },
         )
         .map_err(|e| e.to_string());
@@ -488,8 +948,11 @@ mod tests {
       assert_eq!(
         err,
         Err(
-          ConfigError::UnresolvedConfig {
-            config_type: String::from("extended config"),
+          ConfigError::UnresolvedExtends {
+            candidates: vec![
+              String::from("@parcel/config-default"),
+              String::from("@parcel/config-default/.parcelrc")
+            ],
             from: config.base_config.path,
             specifier: String::from("@parcel/config-default"),
             source: Box::new(ResolveError::NotFound(String::from(""), String::from(""))),
@@ -520,6 +983,7 @@ mod tests {
             additional_reporters: Vec::new(),
             config: Some("@scope/config"),
             fallback_config: None,
+            ..LoadConfigOptions::default()
           },
         )
         .map_err(|e| e.to_string());
@@ -554,6 +1018,7 @@ mod tests {
             additional_reporters: Vec::new(),
             config: Some(&specifier),
             fallback_config: None,
+            ..LoadConfigOptions::default()
           },
         )
         .map_err(|e| e.to_string());
@@ -585,6 +1050,7 @@ mod tests {
             additional_reporters: Vec::new(),
             config: None,
             fallback_config: Some("@parcel/config-default"),
+            ..LoadConfigOptions::default()
           },
         )
         .map_err(|e| e.to_string());
@@ -622,6 +1088,7 @@ mod tests {
             additional_reporters: Vec::new(),
             config: None,
             fallback_config: Some(&fallback_specifier),
+            ..LoadConfigOptions::default()
           },
         )
         .map_err(|e| e.to_string());
@@ -629,8 +1096,11 @@ mod tests {
       assert_eq!(
         err,
         Err(
-          ConfigError::UnresolvedConfig {
-            config_type: String::from("extended config"),
+          ConfigError::UnresolvedExtends {
+            candidates: vec![
+              String::from("@parcel/config-default"),
+              String::from("@parcel/config-default/.parcelrc")
+            ],
             from: fallback.base_config.path,
             specifier: String::from("@parcel/config-default"),
             source: Box::new(ResolveError::NotFound(String::from(""), String::from(""))),
@@ -659,6 +1129,7 @@ mod tests {
             additional_reporters: Vec::new(),
             config: None,
             fallback_config: Some("@parcel/config-default"),
+            ..LoadConfigOptions::default()
           },
         )
         .map_err(|e| e.to_string());
@@ -697,6 +1168,7 @@ mod tests {
             additional_reporters: Vec::new(),
             config: None,
             fallback_config: Some(&fallback_specifier),
+            ..LoadConfigOptions::default()
           },
         )
         .map_err(|e| e.to_string());
@@ -727,6 +1199,7 @@ mod tests {
             additional_reporters: Vec::new(),
             config: None,
             fallback_config: Some(&fallback_specifier),
+            ..LoadConfigOptions::default()
           },
         )
         .map_err(|e| e.to_string());
@@ -760,6 +1233,7 @@ mod tests {
             additional_reporters: Vec::new(),
             config: Some(&config_specifier),
             fallback_config: Some(&fallback_config_specifier),
+            ..LoadConfigOptions::default()
           },
         )
         .map_err(|e| e.to_string());
@@ -786,6 +1260,7 @@ mod tests {
             additional_reporters: Vec::new(),
             config: Some(&config_specifier),
             fallback_config: Some(&fallback_config_specifier),
+            ..LoadConfigOptions::default()
           },
         )
         .map_err(|e| e.to_string());
@@ -793,4 +1268,997 @@ mod tests {
       assert_eq!(parcel_config, Ok((fallback.parcel_config, files)));
     }
   }
+
+  mod additional_reporters {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::parcel_config_fixtures::default_config;
+
+    #[test]
+    fn prepends_additional_reporters_without_duplicates() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = fs.cwd().unwrap();
+
+      let mut default_config = default_config(&Rc::new(project_root.join(".parcelrc")));
+      let files = vec![default_config.path.clone()];
+
+      fs.write_file(default_config.path, default_config.parcel_rc);
+
+      let cli_reporter = |package_name: &str| PluginNode {
+        package_name: String::from(package_name),
+        resolve_from: Rc::new(project_root.join("index")),
+      };
+
+      let parcel_config = ParcelRcConfigLoader::new(&fs, &MockPackageManager::default())
+        .load(
+          &project_root,
+          LoadConfigOptions {
+            additional_reporters: vec![
+              cli_reporter("@parcel/reporter-cli"),
+              cli_reporter("@parcel/reporter-dev-server"),
+            ],
+            ..LoadConfigOptions::default()
+          },
+        )
+        .map_err(|e| e.to_string());
+
+      default_config.parcel_config.reporters = vec![
+        cli_reporter("@parcel/reporter-cli"),
+        cli_reporter("@parcel/reporter-dev-server"),
+      ];
+
+      assert_eq!(parcel_config, Ok((default_config.parcel_config, files)));
+    }
+
+    #[test]
+    fn keeps_relative_reporters_from_other_directories() {
+      let reporter = |package_name: &str, resolve_from: &str| PluginNode {
+        package_name: String::from(package_name),
+        resolve_from: Rc::new(PathBuf::from(resolve_from)),
+      };
+
+      assert_eq!(
+        prepend_reporters(
+          vec![
+            reporter("./reporter.js", "/project/packages/app/index"),
+            reporter("@parcel/reporter-cli", "/project/packages/app/index"),
+          ],
+          vec![
+            reporter("./reporter.js", "/project/.parcelrc"),
+            reporter("./reporter.js", "/project/base.parcelrc"),
+            reporter("@parcel/reporter-cli", "/project/.parcelrc"),
+          ]
+        ),
+        vec![
+          reporter("./reporter.js", "/project/packages/app/index"),
+          reporter("@parcel/reporter-cli", "/project/packages/app/index"),
+          reporter("./reporter.js", "/project/.parcelrc"),
+        ]
+      );
+    }
+  }
+
+  mod load_traced {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::merge_trace::MergeStep;
+    use crate::parcel_config::PluginCategory;
+
+    #[test]
+    fn traces_spreads_filled_by_a_grandparent() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = fs.cwd().unwrap();
+
+      fs.write_file(
+        project_root.join(".parcelrc"),
+        String::from(
+          r#"{
+            "extends": "./base.parcelrc",
+            "transformers": { "*.js": ["parcel-transformer-a", "...", "parcel-transformer-c"] }
+          }"#,
+        ),
+      );
+      fs.write_file(
+        project_root.join("base.parcelrc"),
+        String::from(
+          r#"{
+            "extends": "./default.parcelrc",
+            "transformers": { "*.css": ["parcel-transformer-d"] }
+          }"#,
+        ),
+      );
+      fs.write_file(
+        project_root.join("default.parcelrc"),
+        String::from(
+          r#"{
+            "bundler": "parcel-bundler-default",
+            "namers": ["parcel-namer-default"],
+            "resolvers": ["parcel-resolver-default"],
+            "transformers": { "*.js": ["parcel-transformer-b"] }
+          }"#,
+        ),
+      );
+
+      let plugin = |package_name: &str, file_name: &str| PluginNode {
+        package_name: String::from(package_name),
+        resolve_from: Rc::new(project_root.join(file_name)),
+      };
+
+      let (parcel_config, trace) = ParcelRcConfigLoader::new(&fs, &MockPackageManager::default())
+        .load_traced(&project_root, LoadConfigOptions::default())
+        .unwrap();
+
+      let expanded = vec![
+        plugin("parcel-transformer-a", ".parcelrc"),
+        plugin("parcel-transformer-b", "default.parcelrc"),
+        plugin("parcel-transformer-c", ".parcelrc"),
+      ];
+
+      assert_eq!(
+        parcel_config
+          .transformers
+          .get(Path::new("index.js"), &None::<String>),
+        expanded
+      );
+      assert_eq!(
+        trace.steps(PluginCategory::Transformer, "*.js"),
+        [
+          MergeStep {
+            pipeline: Vec::new(),
+            extended: vec![plugin("parcel-transformer-b", "default.parcelrc")],
+            merged: vec![plugin("parcel-transformer-b", "default.parcelrc")],
+          },
+          MergeStep {
+            pipeline: vec![
+              plugin("parcel-transformer-a", ".parcelrc"),
+              plugin("...", ".parcelrc"),
+              plugin("parcel-transformer-c", ".parcelrc"),
+            ],
+            extended: vec![plugin("parcel-transformer-b", "default.parcelrc")],
+            merged: expanded,
+          },
+        ]
+      );
+      assert_eq!(
+        trace.contributors(PluginCategory::Transformer, "*.js"),
+        vec![
+          project_root.join("default.parcelrc").as_path(),
+          project_root.join(".parcelrc").as_path(),
+        ]
+      );
+    }
+  }
+
+  mod resolve_from {
+    use super::*;
+
+    fn resolve_from(cwd: PathBuf, project_root: &PathBuf) -> PathBuf {
+      let mut fs = InMemoryFileSystem::default();
+      fs.set_current_working_directory(cwd);
+
+      ParcelRcConfigLoader::new(&fs, &MockPackageManager::default()).resolve_from(project_root)
+    }
+
+    #[test]
+    fn returns_cwd_when_inside_project_root() {
+      let project_root = PathBuf::from("/project");
+      let cwd = project_root.join("packages").join("app");
+
+      assert_eq!(resolve_from(cwd.clone(), &project_root), cwd.join("index"));
+    }
+
+    #[test]
+    fn returns_cwd_when_equal_to_project_root() {
+      let project_root = PathBuf::from("/project");
+
+      assert_eq!(
+        resolve_from(project_root.clone(), &project_root),
+        project_root.join("index")
+      );
+    }
+
+    #[test]
+    fn returns_project_root_when_cwd_is_outside() {
+      let project_root = PathBuf::from("/project");
+
+      assert_eq!(
+        resolve_from(PathBuf::from("/"), &project_root),
+        project_root.join("index")
+      );
+
+      assert_eq!(
+        resolve_from(PathBuf::from("/other"), &project_root),
+        project_root.join("index")
+      );
+
+      assert_eq!(
+        resolve_from(PathBuf::from("/project-other"), &project_root),
+        project_root.join("index")
+      );
+    }
+  }
+
+  mod resolve_extends {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::parcel_config_fixtures::default_config;
+
+    #[test]
+    fn normalizes_relative_extends() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = fs.cwd().unwrap();
+
+      let base_config = default_config(&Rc::new(project_root.join("shared").join("base.parcelrc")));
+      let files = vec![project_root.join(".parcelrc"), base_config.path.clone()];
+
+      fs.write_file(
+        project_root.join(".parcelrc"),
+        String::from(r#"{ "extends": "./config/../shared/./base.parcelrc" }"#),
+      );
+      fs.write_file(base_config.path, base_config.parcel_rc);
+
+      let parcel_config = ParcelRcConfigLoader::new(&fs, &MockPackageManager::default())
+        .load(&project_root, LoadConfigOptions::default())
+        .map_err(|e| e.to_string());
+
+      let mut expected = base_config.parcel_config;
+      expected.extends_depths = HashMap::from([(files[0].clone(), 0), (files[1].clone(), 1)]);
+
+      assert_eq!(parcel_config, Ok((expected, files)));
+    }
+
+    #[test]
+    fn normalizes_package_extends() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = fs.cwd().unwrap();
+
+      let package_dir = project_root
+        .join("node_modules")
+        .join("@scope")
+        .join("config");
+      let base_config = default_config(&Rc::new(package_dir.join(".parcelrc")));
+      let files = vec![project_root.join(".parcelrc"), base_config.path.clone()];
+
+      fs.write_file(
+        project_root.join(".parcelrc"),
+        String::from(r#"{ "extends": "@scope/config" }"#),
+      );
+      fs.write_file(base_config.path, base_config.parcel_rc);
+
+      let mut package_manager = MockPackageManager::new();
+      package_manager
+        .expect_resolve()
+        .returning(move |_specifier, _from| {
+          Ok(Resolution {
+            resolved: package_dir
+              .join("lib")
+              .join("..")
+              .join(".")
+              .join(".parcelrc"),
+          })
+        });
+
+      let parcel_config = ParcelRcConfigLoader::new(&fs, &package_manager)
+        .load(&project_root, LoadConfigOptions::default())
+        .map_err(|e| e.to_string());
+
+      let mut expected = base_config.parcel_config;
+      expected.extends_depths = HashMap::from([(files[0].clone(), 0), (files[1].clone(), 1)]);
+
+      assert_eq!(parcel_config, Ok((expected, files)));
+    }
+
+    #[test]
+    fn prefers_earlier_extends() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = fs.cwd().unwrap();
+
+      fs.write_file(
+        project_root.join(".parcelrc"),
+        String::from(r#"{ "extends": ["./a.parcelrc", "./b.parcelrc"] }"#),
+      );
+      fs.write_file(
+        project_root.join("a.parcelrc"),
+        String::from(
+          r#"{ "bundler": "@scope/parcel-bundler-a", "namers": ["@scope/parcel-namer-a"] }"#,
+        ),
+      );
+      fs.write_file(
+        project_root.join("b.parcelrc"),
+        String::from(
+          r#"{
+            "bundler": "@scope/parcel-bundler-b",
+            "namers": ["@scope/parcel-namer-b"],
+            "resolvers": ["@scope/parcel-resolver-b"]
+          }"#,
+        ),
+      );
+
+      let (parcel_config, files) = ParcelRcConfigLoader::new(&fs, &MockPackageManager::default())
+        .load(&project_root, LoadConfigOptions::default())
+        .unwrap();
+
+      assert_eq!(
+        files,
+        vec![
+          project_root.join(".parcelrc"),
+          project_root.join("a.parcelrc"),
+          project_root.join("b.parcelrc"),
+        ]
+      );
+      assert_eq!(
+        parcel_config.bundler,
+        PluginNode {
+          package_name: String::from("@scope/parcel-bundler-a"),
+          resolve_from: Rc::new(project_root.join("a.parcelrc")),
+        }
+      );
+      assert_eq!(
+        parcel_config.namers,
+        vec![PluginNode {
+          package_name: String::from("@scope/parcel-namer-a"),
+          resolve_from: Rc::new(project_root.join("a.parcelrc")),
+        }]
+      );
+      assert_eq!(
+        parcel_config.resolvers,
+        vec![PluginNode {
+          package_name: String::from("@scope/parcel-resolver-b"),
+          resolve_from: Rc::new(project_root.join("b.parcelrc")),
+        }]
+      );
+    }
+
+    #[test]
+    fn resolves_parcelrc_within_package() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = fs.cwd().unwrap();
+
+      let base_config = default_config(&Rc::new(
+        project_root
+          .join("node_modules")
+          .join("@scope")
+          .join("config")
+          .join(".parcelrc"),
+      ));
+      let files = vec![project_root.join(".parcelrc"), base_config.path.clone()];
+
+      fs.write_file(
+        project_root.join(".parcelrc"),
+        String::from(r#"{ "extends": "@scope/config" }"#),
+      );
+      fs.write_file(base_config.path, base_config.parcel_rc);
+
+      let parcel_config = ParcelRcConfigLoader::new(&fs, &InMemoryPackageManager::new(&fs))
+        .load(&project_root, LoadConfigOptions::default())
+        .map_err(|e| e.to_string());
+
+      let mut expected = base_config.parcel_config;
+      expected.extends_depths = HashMap::from([(files[0].clone(), 0), (files[1].clone(), 1)]);
+
+      assert_eq!(parcel_config, Ok((expected, files)));
+    }
+  }
+
+  mod find_config {
+    use std::cell::Cell;
+
+    use super::*;
+
+    /// Counts the files checked while searching for configs
+    struct CountingFileSystem {
+      fs: RefCell<InMemoryFileSystem>,
+      is_file_calls: Cell<usize>,
+    }
+
+    impl FileSystem for CountingFileSystem {
+      fn read_to_string<P: AsRef<Path>>(&self, path: P) -> std::io::Result<String> {
+        self.fs.borrow().read_to_string(path)
+      }
+
+      fn is_file<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.is_file_calls.set(self.is_file_calls.get() + 1);
+        self.fs.borrow().is_file(path)
+      }
+
+      fn is_dir<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.fs.borrow().is_dir(path)
+      }
+    }
+
+    fn counting_fs() -> CountingFileSystem {
+      let mut fs = InMemoryFileSystem::default();
+      fs.write_file("/project/.parcelrc", "{}");
+
+      CountingFileSystem {
+        fs: RefCell::new(fs),
+        is_file_calls: Cell::new(0),
+      }
+    }
+
+    #[test]
+    fn searches_ancestors_once_for_repeated_lookups() {
+      let fs = counting_fs();
+      let package_manager = MockPackageManager::new();
+      let loader = ParcelRcConfigLoader::new(&fs, &package_manager);
+      let project_root = PathBuf::from("/project");
+      let from = PathBuf::from("/project/packages/a/index");
+
+      let expected = PathBuf::from("/project/.parcelrc");
+      assert_eq!(loader.find_config(&project_root, &from).unwrap(), expected);
+
+      let is_file_calls = fs.is_file_calls.get();
+      assert!(is_file_calls > 0);
+
+      assert_eq!(loader.find_config(&project_root, &from).unwrap(), expected);
+      assert_eq!(fs.is_file_calls.get(), is_file_calls);
+    }
+
+    #[test]
+    fn searches_again_after_parcelrc_is_created() {
+      let fs = counting_fs();
+      let package_manager = MockPackageManager::new();
+      let loader = ParcelRcConfigLoader::new(&fs, &package_manager);
+      let project_root = PathBuf::from("/project");
+      let from = PathBuf::from("/project/packages/a/index");
+
+      loader.find_config(&project_root, &from).unwrap();
+
+      let parcel_rc = PathBuf::from("/project/packages/a/.parcelrc");
+      fs.fs.borrow_mut().write_file(&parcel_rc, "{}");
+
+      loader.invalidate(&FileEvent::Update(PathBuf::from(
+        "/project/packages/a/index.js",
+      )));
+      assert_eq!(
+        loader.find_config(&project_root, &from).unwrap(),
+        PathBuf::from("/project/.parcelrc")
+      );
+
+      loader.invalidate(&FileEvent::Create(parcel_rc.clone()));
+      assert_eq!(loader.find_config(&project_root, &from).unwrap(), parcel_rc);
+    }
+  }
+
+  mod max_extends_depth {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::parcel_config_fixtures::default_config;
+
+    /// Writes configs at /0.parcelrc to /{depth}.parcelrc, each extending the next
+    fn extends_chain(depth: usize) -> InMemoryFileSystem {
+      let mut fs = InMemoryFileSystem::default();
+      for index in 0..depth {
+        fs.write_file(
+          format!("/{}.parcelrc", index),
+          format!(r#"{{ "extends": "./{}.parcelrc" }}"#, index + 1),
+        );
+      }
+
+      let last_path = PathBuf::from(format!("/{}.parcelrc", depth));
+      fs.write_file(
+        &last_path,
+        default_config(&Rc::new(last_path.clone())).parcel_rc,
+      );
+
+      fs
+    }
+
+    #[test]
+    fn loads_chains_up_to_the_maximum_depth() {
+      let fs = extends_chain(3);
+      let package_manager = MockPackageManager::new();
+
+      let files = ParcelRcConfigLoader::new(&fs, &package_manager)
+        .with_max_extends_depth(3)
+        .load_config(
+          PathBuf::from("/0.parcelrc"),
+          &HashMap::new(),
+          &[],
+          &mut MergeTrace::default(),
+        )
+        .map(|(_config, files)| files)
+        .map_err(|e| e.to_string());
+
+      assert_eq!(
+        files,
+        Ok(
+          (0..=3)
+            .map(|index| PathBuf::from(format!("/{}.parcelrc", index)))
+            .collect()
+        )
+      );
+    }
+
+    #[test]
+    fn errors_on_chains_past_the_maximum_depth() {
+      let fs = extends_chain(3);
+      let package_manager = MockPackageManager::new();
+
+      let config = ParcelRcConfigLoader::new(&fs, &package_manager)
+        .with_max_extends_depth(2)
+        .load_config(
+          PathBuf::from("/0.parcelrc"),
+          &HashMap::new(),
+          &[],
+          &mut MergeTrace::default(),
+        )
+        .map_err(|e| e.to_string());
+
+      assert_eq!(
+        config,
+        Err(String::from(
+          "Extends are nested deeper than the maximum depth of 2: /0.parcelrc -> /1.parcelrc -> /2.parcelrc -> /3.parcelrc"
+        ))
+      );
+    }
+
+    #[test]
+    fn defaults_to_a_depth_of_ten() {
+      let fs = extends_chain(11);
+      let package_manager = MockPackageManager::new();
+
+      let config = ParcelRcConfigLoader::new(&fs, &package_manager)
+        .load_config(
+          PathBuf::from("/0.parcelrc"),
+          &HashMap::new(),
+          &[],
+          &mut MergeTrace::default(),
+        )
+        .map_err(|e| e.to_string());
+
+      assert!(config.is_err_and(|e| e.contains("maximum depth of 10")));
+    }
+  }
+
+  mod config_loader {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::parcel_config_fixtures::default_config;
+
+    fn load_js_config(path: &Path) -> Result<ParcelRc, Box<dyn std::error::Error>> {
+      assert_eq!(path, Path::new("/project/.parcelrc.js"));
+
+      Ok(serde_json5::from_str(
+        r#"{ "extends": "./base.parcelrc", "reporters": ["@parcel/reporter-js-config"] }"#,
+      )?)
+    }
+
+    #[test]
+    fn loads_configs_with_registered_extensions() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = PathBuf::from("/project");
+      fs.set_current_working_directory(project_root.clone());
+
+      let js_config_path = project_root.join(".parcelrc.js");
+      let base_config_path = project_root.join("base.parcelrc");
+      let base_config = default_config(&Rc::new(base_config_path.clone()));
+      fs.write_file(&js_config_path, "export default () => ({});");
+      fs.write_file(&base_config_path, base_config.parcel_rc);
+
+      let package_manager = MockPackageManager::new();
+      let parcel_config = ParcelRcConfigLoader::new(&fs, &package_manager)
+        .with_config_loader("js", &load_js_config)
+        .load(&project_root, LoadConfigOptions::default())
+        .map(|(config, files)| {
+          (
+            config
+              .reporters()
+              .unwrap()
+              .iter()
+              .map(|r| r.package_name.clone())
+              .collect::<Vec<String>>(),
+            files,
+          )
+        })
+        .map_err(|e| e.to_string());
+
+      assert_eq!(
+        parcel_config,
+        Ok((
+          vec![String::from("@parcel/reporter-js-config")],
+          vec![js_config_path, base_config_path]
+        ))
+      );
+    }
+
+    #[test]
+    fn prefers_parcelrc_over_registered_extensions() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = fs.cwd().unwrap();
+      let parcel_rc_path = project_root.join(".parcelrc");
+      let parcel_rc = default_config(&Rc::new(parcel_rc_path.clone()));
+      fs.write_file(&project_root.join(".parcelrc.js"), "");
+      fs.write_file(&parcel_rc_path, parcel_rc.parcel_rc);
+
+      let package_manager = MockPackageManager::new();
+      let parcel_config = ParcelRcConfigLoader::new(&fs, &package_manager)
+        .with_config_loader("js", &load_js_config)
+        .load(&project_root, LoadConfigOptions::default())
+        .map_err(|e| e.to_string());
+
+      assert_eq!(
+        parcel_config,
+        Ok((parcel_rc.parcel_config, vec![parcel_rc_path]))
+      );
+    }
+
+    #[test]
+    fn errors_when_the_loader_fails() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = fs.cwd().unwrap();
+      fs.write_file(&project_root.join(".parcelrc.js"), "");
+
+      let package_manager = MockPackageManager::new();
+      let parcel_config = ParcelRcConfigLoader::new(&fs, &package_manager)
+        .with_config_loader("js", &|_path| Err("Syntax error".into()))
+        .load(&project_root, LoadConfigOptions::default())
+        .map_err(|e| e.to_string());
+
+      assert_eq!(
+        parcel_config,
+        Err(String::from("Failed to load /.parcelrc.js"))
+      );
+    }
+  }
+
+  #[cfg(feature = "http-extends")]
+  mod remote_extends {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::config_fetcher::MockConfigFetcher;
+    use crate::parcel_config::PluginCategory;
+    use crate::parcel_config_fixtures::default_config;
+
+    const URL: &str = "https://example.com/.parcelrc";
+
+    #[test]
+    fn merges_fetched_configs() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = fs.cwd().unwrap();
+      let parcel_rc_path = project_root.join(".parcelrc");
+
+      // Plugins of the remote config are resolved from the directory of the config that extends it
+      let remote_path = project_root.join(URL);
+      let mut remote_config = default_config(&Rc::new(remote_path.clone()));
+      let remote_parcel_rc = remote_config.parcel_rc.clone();
+
+      fs.write_file(&parcel_rc_path, format!(r#"{{ "extends": "{URL}" }}"#));
+
+      let mut fetcher = MockConfigFetcher::new();
+      fetcher
+        .expect_fetch()
+        .with(mockall::predicate::eq(URL))
+        .times(1)
+        .returning(move |_| Ok(remote_parcel_rc.clone()));
+
+      let package_manager = MockPackageManager::default();
+      let parcel_config = ParcelRcConfigLoader::new(&fs, &package_manager)
+        .with_fetcher(&fetcher)
+        .load(&project_root, LoadConfigOptions::default())
+        .map_err(|e| e.to_string());
+
+      remote_config.parcel_config.extends_depths =
+        HashMap::from([(parcel_rc_path.clone(), 0), (remote_path, 1)]);
+
+      assert_eq!(
+        parcel_config,
+        Ok((remote_config.parcel_config, vec![parcel_rc_path]))
+      );
+    }
+
+    #[test]
+    fn validates_plugin_names_of_fetched_configs() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = fs.cwd().unwrap();
+
+      fs.write_file(
+        project_root.join(".parcelrc"),
+        format!(r#"{{ "extends": "{URL}" }}"#),
+      );
+
+      let mut fetcher = MockConfigFetcher::new();
+      fetcher.expect_fetch().returning(|_| {
+        Ok(String::from(
+          r#"{
+  "reporters": ["parcel-cli-reporter"]
+}"#,
+        ))
+      });
+
+      let package_manager = MockPackageManager::default();
+      let parcel_config = ParcelRcConfigLoader::new(&fs, &package_manager)
+        .with_fetcher(&fetcher)
+        .load(&project_root, LoadConfigOptions::default())
+        .map_err(|e| e.to_string());
+
+      assert_eq!(
+        parcel_config,
+        Err(format!(
+          "Invalid plugin name parcel-cli-reporter at {URL}:2:17: Parcel reporter packages must be named according to \"parcel-reporter-{{name}}\""
+        ))
+      );
+    }
+
+    #[test]
+    fn reports_fetched_plugins_one_extends_away() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = fs.cwd().unwrap();
+
+      fs.write_file(
+        project_root.join(".parcelrc"),
+        format!(r#"{{ "extends": "{URL}", "reporters": ["...", "@parcel/reporter-cli"] }}"#),
+      );
+
+      let mut fetcher = MockConfigFetcher::new();
+      fetcher.expect_fetch().returning(|_| {
+        Ok(String::from(
+          r#"{
+            "bundler": "@parcel/bundler-default",
+            "namers": ["@parcel/namer-default"],
+            "reporters": ["@parcel/reporter-dev-server"],
+            "resolvers": ["@parcel/resolver-default"]
+          }"#,
+        ))
+      });
+
+      let package_manager = MockPackageManager::default();
+      let (parcel_config, files) = ParcelRcConfigLoader::new(&fs, &package_manager)
+        .with_fetcher(&fetcher)
+        .load(&project_root, LoadConfigOptions::default())
+        .unwrap();
+
+      let plugin = |package_name: &str, resolve_from: PathBuf| PluginNode {
+        package_name: String::from(package_name),
+        resolve_from: Rc::new(resolve_from),
+      };
+
+      assert_eq!(files, vec![project_root.join(".parcelrc")]);
+      assert_eq!(
+        parcel_config.transitive_plugins(PluginCategory::Reporter),
+        vec![
+          (
+            plugin("@parcel/reporter-dev-server", project_root.join(URL)),
+            1
+          ),
+          (
+            plugin("@parcel/reporter-cli", project_root.join(".parcelrc")),
+            0
+          ),
+        ]
+      );
+    }
+
+    #[test]
+    fn errors_on_relative_extends_of_fetched_configs() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = fs.cwd().unwrap();
+
+      fs.write_file(
+        project_root.join(".parcelrc"),
+        format!(r#"{{ "extends": "{URL}" }}"#),
+      );
+      fs.write_file(
+        project_root.join("base.parcelrc"),
+        default_config(&Rc::new(project_root.join("base.parcelrc"))).parcel_rc,
+      );
+
+      let mut fetcher = MockConfigFetcher::new();
+      fetcher
+        .expect_fetch()
+        .returning(|_| Ok(String::from(r#"{ "extends": "./base.parcelrc" }"#)));
+
+      let package_manager = MockPackageManager::default();
+      let parcel_config = ParcelRcConfigLoader::new(&fs, &package_manager)
+        .with_fetcher(&fetcher)
+        .load(&project_root, LoadConfigOptions::default())
+        .map_err(|e| e.to_string());
+
+      assert_eq!(
+        parcel_config,
+        Err(format!(
+          "Failed to resolve extended config ./base.parcelrc from {URL}, tried ./base.parcelrc"
+        ))
+      );
+    }
+
+    #[test]
+    fn errors_without_a_fetcher() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = fs.cwd().unwrap();
+      let parcel_rc_path = project_root.join(".parcelrc");
+
+      fs.write_file(&parcel_rc_path, format!(r#"{{ "extends": "{URL}" }}"#));
+
+      let package_manager = MockPackageManager::default();
+      let parcel_config = ParcelRcConfigLoader::new(&fs, &package_manager)
+        .load(&project_root, LoadConfigOptions::default())
+        .map_err(|e| e.to_string());
+
+      assert_eq!(
+        parcel_config,
+        Err(format!(
+          "Failed to resolve extended config {URL} from {}, tried {URL}",
+          parcel_rc_path.display()
+        ))
+      );
+    }
+  }
+
+  mod interpolate_env {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::parcel_config_fixtures::default_config;
+    use crate::parcel_rc::Extends;
+    use crate::parcel_rc::PluginEntry;
+
+    fn env() -> HashMap<String, String> {
+      HashMap::from([(String::from("CONFIG_DIR"), String::from("/config"))])
+    }
+
+    #[test]
+    fn replaces_defined_variables() {
+      assert_eq!(
+        interpolate_env(
+          Path::new(".parcelrc"),
+          r#"{ "extends": "${CONFIG_DIR}/base.parcelrc" }"#,
+          &env()
+        )
+        .map_err(|e| e.to_string()),
+        Ok(String::from(r#"{ "extends": "/config/base.parcelrc" }"#))
+      );
+    }
+
+    #[test]
+    fn errors_on_undefined_variables() {
+      assert_eq!(
+        interpolate_env(
+          Path::new(".parcelrc"),
+          r#"{ "extends": "${MISSING}/base.parcelrc" }"#,
+          &env()
+        )
+        .map_err(|e| e.to_string()),
+        Err(
+          ConfigError::UndefinedVariable {
+            name: String::from("MISSING"),
+            path: PathBuf::from(".parcelrc"),
+          }
+          .to_string()
+        )
+      );
+    }
+
+    #[test]
+    fn keeps_literal_and_escaped_dollars() {
+      assert_eq!(
+        interpolate_env(
+          Path::new(".parcelrc"),
+          r#"{ "reporters": ["$reporter", "$${CONFIG_DIR}", "$$", "$"] }"#,
+          &env()
+        )
+        .map_err(|e| e.to_string()),
+        Ok(String::from(
+          r#"{ "reporters": ["$reporter", "${CONFIG_DIR}", "$", "$"] }"#
+        ))
+      );
+    }
+
+    #[test]
+    fn escapes_values_for_the_string_they_are_placed_in() {
+      let env = HashMap::from([
+        (String::from("CONFIG_DIR"), String::from(r"C:\configs")),
+        (String::from("QUOTED"), String::from(r#"a "b" 'c'"#)),
+      ]);
+
+      let interpolated = interpolate_env(
+        Path::new(".parcelrc"),
+        r#"{ "extends": "${CONFIG_DIR}\\base.parcelrc", "reporters": ["${QUOTED}", '${QUOTED}'] }"#,
+        &env,
+      )
+      .unwrap();
+
+      let parcel_rc: ParcelRc = serde_json5::from_str(&interpolated).unwrap();
+
+      assert!(matches!(
+        parcel_rc.extends,
+        Some(Extends::One(extends)) if extends == r"C:\configs\base.parcelrc"
+      ));
+      assert_eq!(
+        parcel_rc
+          .reporters
+          .iter()
+          .flatten()
+          .filter_map(PluginEntry::package_name)
+          .collect::<Vec<&str>>(),
+        vec![r#"a "b" 'c'"#, r#"a "b" 'c'"#]
+      );
+    }
+
+    #[test]
+    fn ignores_references_outside_of_strings() {
+      let contents = r#"{
+        // See ${HOME}/.parcelrc
+        /* Or ${MISSING} */
+        "extends": "${CONFIG_DIR}/base.parcelrc"
+      }"#;
+
+      assert_eq!(
+        interpolate_env(Path::new(".parcelrc"), contents, &env()).map_err(|e| e.to_string()),
+        Ok(contents.replace("${CONFIG_DIR}", "/config"))
+      );
+    }
+
+    #[test]
+    fn locates_invalid_plugin_names_before_interpolation() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = fs.cwd().unwrap();
+      let parcel_rc_path = project_root.join(".parcelrc");
+
+      fs.write_file(
+        &parcel_rc_path,
+        String::from(
+          r#"{ "extends": "${CONFIG_DIR}/base.parcelrc", "reporters": ["parcel-cli-reporter"] }"#,
+        ),
+      );
+
+      let parcel_config = ParcelRcConfigLoader::new(&fs, &MockPackageManager::default())
+        .load(
+          &project_root,
+          LoadConfigOptions {
+            env: HashMap::from([(
+              String::from("CONFIG_DIR"),
+              String::from("./a/much/longer/config/dir"),
+            )]),
+            ..LoadConfigOptions::default()
+          },
+        )
+        .map_err(|e| e.to_string());
+
+      assert_eq!(
+        parcel_config,
+        Err(format!(
+          "Invalid plugin name parcel-cli-reporter at {}:1:59: Parcel reporter packages must be named according to \"parcel-reporter-{{name}}\"",
+          parcel_rc_path.display()
+        ))
+      );
+    }
+
+    #[test]
+    fn interpolates_extends_when_loading() {
+      let mut fs = InMemoryFileSystem::default();
+      let project_root = fs.cwd().unwrap();
+      let config_dir = project_root.join("config");
+
+      let base_config = default_config(&Rc::new(config_dir.join("base.parcelrc")));
+      let files = vec![project_root.join(".parcelrc"), base_config.path.clone()];
+
+      fs.write_file(
+        project_root.join(".parcelrc"),
+        String::from(r#"{ "extends": "${CONFIG_DIR}/base.parcelrc" }"#),
+      );
+      fs.write_file(base_config.path, base_config.parcel_rc);
+
+      let parcel_config = ParcelRcConfigLoader::new(&fs, &MockPackageManager::default())
+        .load(
+          &project_root,
+          LoadConfigOptions {
+            env: HashMap::from([(String::from("CONFIG_DIR"), String::from("./config"))]),
+            ..LoadConfigOptions::default()
+          },
+        )
+        .map_err(|e| e.to_string());
+
+      let mut expected = base_config.parcel_config;
+      expected.extends_depths = HashMap::from([(files[0].clone(), 0), (files[1].clone(), 1)]);
+
+      assert_eq!(parcel_config, Ok((expected, files)));
+    }
+  }
 }
@@ -1,12 +1,19 @@
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 use derive_builder::Builder;
 use indexmap::IndexMap;
 
+use super::merge_trace::MergeStep;
+use super::merge_trace::MergeTrace;
+use super::parcel_config::PluginCategory;
 use super::parcel_config::PluginNode;
 use super::parcel_rc::ParcelRcFile;
+use super::parcel_rc::PluginEntry;
 use crate::config_error::ConfigError;
+use crate::config_error::ConfigWarning;
 
 /// An intermediate representation of the .parcelrc config
 ///
@@ -17,6 +24,10 @@ use crate::config_error::ConfigError;
 pub struct PartialParcelConfig {
   pub bundler: Option<PluginNode>,
   pub compressors: IndexMap<String, Vec<PluginNode>>,
+  /// How many extends each config file is from the loaded config, which is at depth 0
+  pub extends_depths: HashMap<PathBuf, usize>,
+  /// Problems with the "extends" of the config files, such as duplicate specifiers
+  pub extends_warnings: Vec<ConfigWarning>,
   pub namers: Vec<PluginNode>,
   pub optimizers: IndexMap<String, Vec<PluginNode>>,
   pub packagers: IndexMap<String, PluginNode>,
@@ -32,31 +43,42 @@ impl TryFrom<&ParcelRcFile> for PartialParcelConfig {
 
   fn try_from(parcel_rc: &ParcelRcFile) -> Result<PartialParcelConfig, ConfigError> {
     // TODO Add validation here: multiple ..., plugin name format, reserved pipelines, etc
+    parcel_rc.validate_plugin_entries()?;
 
+    // Every plugin from the same file shares a single allocation of its path
     let resolve_from = Rc::new(parcel_rc.path.clone());
 
-    let to_entry = |package_name: &String| PluginNode {
+    let to_entry = |package_name: &str| PluginNode {
       package_name: String::from(package_name),
       resolve_from: Rc::clone(&resolve_from),
     };
 
-    let to_vec = |maybe_plugins: Option<&Vec<String>>| {
-      maybe_plugins
-        .map(|plugins| plugins.iter().map(to_entry).collect())
-        .unwrap_or(Vec::new())
+    // Disabled plugins are dropped here, so they take no part in merging
+    let to_entries = |plugins: &Vec<PluginEntry>| {
+      plugins
+        .iter()
+        .filter_map(PluginEntry::package_name)
+        .map(to_entry)
+        .collect()
     };
 
-    let to_pipelines = |map: Option<&IndexMap<String, Vec<String>>>| {
+    let to_vec = |maybe_plugins: Option<&Vec<PluginEntry>>| {
+      maybe_plugins.map(to_entries).unwrap_or(Vec::new())
+    };
+
+    // A pipeline of only disabled plugins is dropped like an empty list elsewhere, rather than
+    // kept as an explicitly empty pipeline
+    let is_disabled = |plugins: &Vec<PluginEntry>| {
+      !plugins.is_empty() && plugins.iter().all(|p| p.package_name().is_none())
+    };
+
+    let to_pipelines = |map: Option<&IndexMap<String, Vec<PluginEntry>>>| {
       map
         .map(|plugins| {
           plugins
             .iter()
-            .map(|(pattern, plugins)| {
-              (
-                String::from(pattern),
-                plugins.iter().map(to_entry).collect(),
-              )
-            })
+            .filter(|(_pattern, plugins)| !is_disabled(plugins))
+            .map(|(pattern, plugins)| (String::from(pattern), to_entries(plugins)))
             .collect()
         })
         .unwrap_or(IndexMap::new())
@@ -73,16 +95,16 @@ impl TryFrom<&ParcelRcFile> for PartialParcelConfig {
         .unwrap_or(IndexMap::new())
     };
 
+    let extends_warnings = match &parcel_rc.contents.extends {
+      None => Vec::new(),
+      Some(extends) => extends.specifiers(&parcel_rc.path)?.1,
+    };
+
     Ok(PartialParcelConfig {
-      bundler: parcel_rc
-        .contents
-        .bundler
-        .as_ref()
-        .map(|package_name| PluginNode {
-          package_name: String::from(package_name),
-          resolve_from: Rc::clone(&resolve_from),
-        }),
+      bundler: parcel_rc.contents.bundler.as_deref().map(to_entry),
       compressors: to_pipelines(parcel_rc.contents.compressors.as_ref()),
+      extends_depths: HashMap::from([(parcel_rc.path.clone(), 0)]),
+      extends_warnings,
       namers: to_vec(parcel_rc.contents.namers.as_ref()),
       optimizers: to_pipelines(parcel_rc.contents.optimizers.as_ref()),
       packagers: to_pipeline(parcel_rc.contents.packagers.as_ref()),
@@ -95,12 +117,38 @@ impl TryFrom<&ParcelRcFile> for PartialParcelConfig {
   }
 }
 
+/// The plugin name used to remove a pattern inherited through "extends"
+///
+/// A pattern is removed when its pipeline consists of this marker alone, e.g. `"*.gz": ["null"]`,
+/// or `"*.js": "null"` for packagers. Removal takes precedence over everything the extended
+/// configs define for that pattern, so it cannot be combined with "..." to keep some of the
+/// inherited plugins.
+///
+pub(crate) const REMOVE_PATTERN: &str = "null";
+
 impl PartialParcelConfig {
   fn merge_map<T: Clone>(
     map: IndexMap<String, T>,
     extend_map: IndexMap<String, T>,
     merge: fn(map: T, extend_map: T) -> T,
+    is_removed: fn(value: &T) -> bool,
   ) -> IndexMap<String, T> {
+    let removed_patterns: HashSet<String> = map
+      .iter()
+      .filter(|(_pattern, value)| is_removed(value))
+      .map(|(pattern, _value)| pattern.clone())
+      .collect();
+
+    let (map, extend_map) = if removed_patterns.is_empty() {
+      (map, extend_map)
+    } else {
+      let retain = |(pattern, _value): &(String, T)| !removed_patterns.contains(pattern);
+      (
+        map.into_iter().filter(retain).collect(),
+        extend_map.into_iter().filter(retain).collect(),
+      )
+    };
+
     if extend_map.is_empty() {
       return map;
     }
@@ -137,14 +185,39 @@ impl PartialParcelConfig {
     map: IndexMap<String, PluginNode>,
     extend_map: IndexMap<String, PluginNode>,
   ) -> IndexMap<String, PluginNode> {
-    PartialParcelConfig::merge_map(map, extend_map, |map, _extend_map| map)
+    PartialParcelConfig::merge_map(
+      map,
+      extend_map,
+      |map, _extend_map| map,
+      |plugin| plugin.package_name == REMOVE_PATTERN,
+    )
   }
 
   fn merge_pipelines_map(
     from_map: IndexMap<String, Vec<PluginNode>>,
     extend_map: IndexMap<String, Vec<PluginNode>>,
   ) -> IndexMap<String, Vec<PluginNode>> {
-    PartialParcelConfig::merge_map(from_map, extend_map, PartialParcelConfig::merge_pipelines)
+    PartialParcelConfig::merge_map(
+      from_map,
+      extend_map,
+      PartialParcelConfig::merge_pattern_pipelines,
+      |pipelines| matches!(pipelines.as_slice(), [plugin] if plugin.package_name == REMOVE_PATTERN),
+    )
+  }
+
+  /// Merges the pipelines of a pattern that both configs define
+  ///
+  /// Unlike phases without patterns, an empty pipeline for a pattern was written explicitly, so it
+  /// is kept rather than replaced with the extended pipeline.
+  fn merge_pattern_pipelines(
+    from_pipelines: Vec<PluginNode>,
+    extend_pipelines: Vec<PluginNode>,
+  ) -> Vec<PluginNode> {
+    if from_pipelines.is_empty() {
+      return from_pipelines;
+    }
+
+    PartialParcelConfig::merge_pipelines(from_pipelines, extend_pipelines)
   }
 
   fn merge_pipelines(
@@ -178,13 +251,109 @@ impl PartialParcelConfig {
     }
   }
 
+  /// Returns the pattern maps that hold pipelines, keyed by their plugin category
+  fn pipeline_maps(&self) -> [(PluginCategory, &IndexMap<String, Vec<PluginNode>>); 4] {
+    [
+      (PluginCategory::Compressor, &self.compressors),
+      (PluginCategory::Optimizer, &self.optimizers),
+      (PluginCategory::Transformer, &self.transformers),
+      (PluginCategory::Validator, &self.validators),
+    ]
+  }
+
+  /// Moves every file of the config one extends further from the loaded config
+  ///
+  /// This is applied to each config that is extended before it is merged, since configs that are
+  /// extended together are at the same depth.
+  ///
+  pub fn into_extended(mut self) -> Self {
+    for depth in self.extends_depths.values_mut() {
+      *depth += 1;
+    }
+
+    self
+  }
+
+  /// Combines the depths of both configs, keeping the shallowest depth of a file in both
+  fn merge_extends_depths(
+    from_depths: HashMap<PathBuf, usize>,
+    extend_depths: HashMap<PathBuf, usize>,
+  ) -> HashMap<PathBuf, usize> {
+    let mut depths = from_depths;
+    for (path, depth) in extend_depths {
+      let merged_depth = depths.entry(path).or_insert(depth);
+      *merged_depth = depth.min(*merged_depth);
+    }
+
+    depths
+  }
+
+  /// Combines the warnings of both configs, keeping one of each for files extended more than once
+  fn merge_extends_warnings(
+    from_warnings: Vec<ConfigWarning>,
+    extend_warnings: Vec<ConfigWarning>,
+  ) -> Vec<ConfigWarning> {
+    let mut warnings = from_warnings;
+    for warning in extend_warnings {
+      if !warnings.contains(&warning) {
+        warnings.push(warning);
+      }
+    }
+
+    warnings
+  }
+
   pub fn merge(from_config: PartialParcelConfig, extend_config: PartialParcelConfig) -> Self {
-    PartialParcelConfig {
+    PartialParcelConfig::merge_traced(from_config, extend_config).0
+  }
+
+  /// Merges two configs like [`merge`](Self::merge), recording how each pipeline was merged
+  ///
+  /// A trace for a chain of extended configs can be built by appending the trace of each merge.
+  ///
+  pub fn merge_traced(
+    from_config: PartialParcelConfig,
+    extend_config: PartialParcelConfig,
+  ) -> (Self, MergeTrace) {
+    // The pipelines are moved into the merged config, so each side is recorded before merging
+    let mut unmerged = Vec::new();
+    let maps = from_config
+      .pipeline_maps()
+      .into_iter()
+      .zip(extend_config.pipeline_maps());
+
+    for ((category, from_map), (_, extend_map)) in maps {
+      let patterns = from_map
+        .keys()
+        .chain(extend_map.keys().filter(|p| !from_map.contains_key(*p)));
+
+      for pattern in patterns {
+        let pipeline =
+          |map: &IndexMap<String, Vec<PluginNode>>| map.get(pattern).cloned().unwrap_or_default();
+
+        unmerged.push((
+          category,
+          pattern.clone(),
+          pipeline(from_map),
+          pipeline(extend_map),
+        ));
+      }
+    }
+
+    let merged_config = PartialParcelConfig {
       bundler: from_config.bundler.or(extend_config.bundler),
       compressors: PartialParcelConfig::merge_pipelines_map(
         from_config.compressors,
         extend_config.compressors,
       ),
+      extends_depths: PartialParcelConfig::merge_extends_depths(
+        from_config.extends_depths,
+        extend_config.extends_depths,
+      ),
+      extends_warnings: PartialParcelConfig::merge_extends_warnings(
+        from_config.extends_warnings,
+        extend_config.extends_warnings,
+      ),
       namers: PartialParcelConfig::merge_pipelines(from_config.namers, extend_config.namers),
       optimizers: PartialParcelConfig::merge_pipelines_map(
         from_config.optimizers,
@@ -211,7 +380,29 @@ impl PartialParcelConfig {
         from_config.validators,
         extend_config.validators,
       ),
+    };
+
+    let mut trace = MergeTrace::default();
+    let merged_maps = merged_config.pipeline_maps();
+    for (category, pattern, pipeline, extended) in unmerged {
+      let merged = merged_maps
+        .iter()
+        .find(|(merged_category, _map)| *merged_category == category)
+        .and_then(|(_category, map)| map.get(&pattern).cloned())
+        .unwrap_or_default();
+
+      trace.push(
+        category,
+        &pattern,
+        MergeStep {
+          pipeline,
+          extended,
+          merged,
+        },
+      );
     }
+
+    (merged_config, trace)
   }
 }
 
@@ -369,6 +560,70 @@ mod tests {
             );
           }
 
+          #[test]
+          fn removes_patterns_with_null_pipeline() {
+            let from = PartialParcelConfigBuilder::default()
+              .$property(indexmap! {
+                String::from("*.js") => vec!(PluginNode {
+                  package_name: String::from("null"),
+                  resolve_from: Rc::new(PathBuf::from("/")),
+                })
+              })
+              .build()
+              .unwrap();
+
+            let extend = PartialParcelConfigBuilder::default()
+              .$property(indexmap! {
+                String::from("*.css") => vec!(PluginNode {
+                  package_name: String::from("b"),
+                  resolve_from: Rc::new(PathBuf::from("~")),
+                }),
+                String::from("*.js") => vec!(PluginNode {
+                  package_name: String::from("c"),
+                  resolve_from: Rc::new(PathBuf::from("~")),
+                })
+              })
+              .build()
+              .unwrap();
+
+            assert_eq!(
+              PartialParcelConfig::merge(from, extend),
+              PartialParcelConfigBuilder::default()
+                .$property(indexmap! {
+                  String::from("*.css") => vec!(PluginNode {
+                    package_name: String::from("b"),
+                    resolve_from: Rc::new(PathBuf::from("~")),
+                  }),
+                })
+                .build()
+                .unwrap()
+            );
+          }
+
+          #[test]
+          fn keeps_empty_pipelines() {
+            let from = PartialParcelConfigBuilder::default()
+              .$property(indexmap! {
+                String::from("*.js") => Vec::new()
+              })
+              .build()
+              .unwrap();
+
+            let extend = PartialParcelConfigBuilder::default()
+              .$property(indexmap! {
+                String::from("*.js") => vec!(PluginNode {
+                  package_name: String::from("c"),
+                  resolve_from: Rc::new(PathBuf::from("/")),
+                })
+              })
+              .build()
+              .unwrap();
+
+            let expected = from.clone();
+
+            assert_eq!(PartialParcelConfig::merge(from, extend), expected);
+          }
+
           #[test]
           fn merges_pipelines_with_missing_dot_dot_dot() {
             let from = PartialParcelConfigBuilder::default()
@@ -689,4 +944,238 @@ mod tests {
     test_pipeline_map!(transformers);
     test_pipeline_map!(validators);
   }
+
+  mod try_from {
+    use std::path::PathBuf;
+
+    use indexmap::indexmap;
+
+    use super::*;
+
+    #[test]
+    fn errors_on_enabled_plugin_entries() {
+      let parcel_rc = ParcelRcFile {
+        path: PathBuf::from("/.parcelrc"),
+        contents: serde_json5::from_str(
+          r#"{ "transformers": { "*.js": ["@parcel/transformer-js", true] } }"#,
+        )
+        .unwrap(),
+      };
+
+      assert_eq!(
+        PartialParcelConfig::try_from(&parcel_rc).map_err(|e| e.to_string()),
+        Err(String::from(
+          "Invalid plugin entry true at index 1 of transformers for *.js in /.parcelrc: plugins can only be disabled with false"
+        ))
+      );
+    }
+
+    #[test]
+    fn drops_disabled_plugins() {
+      let parcel_rc = ParcelRcFile {
+        path: PathBuf::from("/.parcelrc"),
+        contents: serde_json5::from_str(
+          r#"{
+            "reporters": ["@parcel/reporter-cli", false],
+            "transformers": {
+              "*.js": [
+                { "plugin": "@parcel/transformer-babel", "disabled": true },
+                { "plugin": "@parcel/transformer-js" },
+                "..."
+              ]
+            }
+          }"#,
+        )
+        .unwrap(),
+      };
+
+      let plugin = |package_name: &str| PluginNode {
+        package_name: String::from(package_name),
+        resolve_from: Rc::new(PathBuf::from("/.parcelrc")),
+      };
+
+      assert_eq!(
+        PartialParcelConfig::try_from(&parcel_rc).map_err(|e| e.to_string()),
+        Ok(
+          PartialParcelConfigBuilder::default()
+            .extends_depths(HashMap::from([(PathBuf::from("/.parcelrc"), 0)]))
+            .reporters(vec![plugin("@parcel/reporter-cli")])
+            .transformers(indexmap! {
+              String::from("*.js") => vec![plugin("@parcel/transformer-js"), plugin("...")]
+            })
+            .build()
+            .unwrap()
+        )
+      );
+    }
+
+    #[test]
+    fn excludes_disabled_plugins_from_merges() {
+      let parcel_rc = ParcelRcFile {
+        path: PathBuf::from("/.parcelrc"),
+        contents: serde_json5::from_str(
+          r#"{
+            "transformers": {
+              "*.js": [{ "plugin": "@parcel/transformer-babel", "disabled": true }, "..."]
+            }
+          }"#,
+        )
+        .unwrap(),
+      };
+
+      let extended = PartialParcelConfigBuilder::default()
+        .transformers(indexmap! {
+          String::from("*.js") => vec![PluginNode {
+            package_name: String::from("@parcel/transformer-js"),
+            resolve_from: Rc::new(PathBuf::from("/base.parcelrc")),
+          }]
+        })
+        .build()
+        .unwrap();
+
+      let config = PartialParcelConfig::merge(
+        PartialParcelConfig::try_from(&parcel_rc).unwrap(),
+        extended.clone(),
+      );
+
+      assert_eq!(config.transformers, extended.transformers);
+    }
+
+    #[test]
+    fn inherits_lists_of_only_disabled_plugins() {
+      let parcel_rc = ParcelRcFile {
+        path: PathBuf::from("/.parcelrc"),
+        contents: serde_json5::from_str(
+          r#"{
+            "reporters": [false],
+            "transformers": {
+              "*.js": [{ "plugin": "@parcel/transformer-babel", "disabled": true }],
+              "*.css": []
+            }
+          }"#,
+        )
+        .unwrap(),
+      };
+
+      let plugin = |package_name: &str| PluginNode {
+        package_name: String::from(package_name),
+        resolve_from: Rc::new(PathBuf::from("/base.parcelrc")),
+      };
+
+      let extended = PartialParcelConfigBuilder::default()
+        .reporters(vec![plugin("@parcel/reporter-cli")])
+        .transformers(indexmap! {
+          String::from("*.js") => vec![plugin("@parcel/transformer-js")],
+          String::from("*.css") => vec![plugin("@parcel/transformer-css")],
+        })
+        .build()
+        .unwrap();
+
+      let config =
+        PartialParcelConfig::merge(PartialParcelConfig::try_from(&parcel_rc).unwrap(), extended);
+
+      assert_eq!(config.reporters, vec![plugin("@parcel/reporter-cli")]);
+      assert_eq!(
+        config.transformers,
+        indexmap! {
+          String::from("*.js") => vec![plugin("@parcel/transformer-js")],
+          String::from("*.css") => Vec::new(),
+        }
+      );
+    }
+  }
+
+  mod merge_traced {
+    use std::path::Path;
+    use std::path::PathBuf;
+
+    use indexmap::indexmap;
+
+    use super::*;
+
+    fn plugin(package_name: &str, resolve_from: &str) -> PluginNode {
+      PluginNode {
+        package_name: String::from(package_name),
+        resolve_from: Rc::new(PathBuf::from(resolve_from)),
+      }
+    }
+
+    #[test]
+    fn traces_spreads_filled_by_a_grandparent() {
+      let from = PartialParcelConfigBuilder::default()
+        .transformers(indexmap! {
+          String::from("*.js") => vec![
+            plugin("a", "/app/.parcelrc"),
+            plugin("...", "/app/.parcelrc"),
+            plugin("c", "/app/.parcelrc"),
+          ]
+        })
+        .build()
+        .unwrap();
+
+      let extend_1 = PartialParcelConfigBuilder::default()
+        .transformers(indexmap! {
+          String::from("*.css") => vec![plugin("d", "/base/.parcelrc")]
+        })
+        .build()
+        .unwrap();
+
+      let extend_2 = PartialParcelConfigBuilder::default()
+        .transformers(indexmap! {
+          String::from("*.js") => vec![plugin("b", "/default/.parcelrc")]
+        })
+        .build()
+        .unwrap();
+
+      let (config, mut trace) = PartialParcelConfig::merge_traced(from, extend_1);
+      let (config, grandparent_trace) = PartialParcelConfig::merge_traced(config, extend_2);
+      trace.append(grandparent_trace);
+
+      let spread = vec![
+        plugin("a", "/app/.parcelrc"),
+        plugin("...", "/app/.parcelrc"),
+        plugin("c", "/app/.parcelrc"),
+      ];
+
+      let expanded = vec![
+        plugin("a", "/app/.parcelrc"),
+        plugin("b", "/default/.parcelrc"),
+        plugin("c", "/app/.parcelrc"),
+      ];
+
+      assert_eq!(config.transformers["*.js"], expanded);
+      assert_eq!(
+        trace.steps(PluginCategory::Transformer, "*.js"),
+        [
+          MergeStep {
+            pipeline: spread.clone(),
+            extended: Vec::new(),
+            merged: spread.clone(),
+          },
+          MergeStep {
+            pipeline: spread,
+            extended: vec![plugin("b", "/default/.parcelrc")],
+            merged: expanded,
+          },
+        ]
+      );
+      assert_eq!(
+        trace
+          .steps(PluginCategory::Transformer, "*.js")
+          .iter()
+          .map(MergeStep::expands_spread)
+          .collect::<Vec<bool>>(),
+        vec![false, true]
+      );
+      assert_eq!(
+        trace.contributors(PluginCategory::Transformer, "*.js"),
+        vec![Path::new("/app/.parcelrc"), Path::new("/default/.parcelrc")]
+      );
+      assert_eq!(
+        trace.contributors(PluginCategory::Transformer, "*.css"),
+        vec![Path::new("/base/.parcelrc")]
+      );
+      assert_eq!(trace.steps(PluginCategory::Optimizer, "*.js"), []);
+    }
+  }
 }
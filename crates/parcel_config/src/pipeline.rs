@@ -1,8 +1,12 @@
+use std::borrow::Cow;
 use std::path::Path;
+use std::path::PathBuf;
 
-use glob_match::glob_match;
 use indexmap::IndexMap;
 
+use super::config_diff::changed_patterns;
+use super::glob_matcher::normalize_glob;
+use super::glob_matcher::GlobMatcher;
 use super::parcel_config::PluginNode;
 
 /// Represents fields in .parcelrc that use an object, mapping a pattern to a list of plugin names
@@ -25,15 +29,19 @@ use super::parcel_config::PluginNode;
 /// });
 /// ```
 ///
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default)]
 pub struct PipelineMap {
   /// Maps patterns to a series of plugins, called pipelines
   map: IndexMap<String, Vec<PluginNode>>,
+  /// The named pipeline and matcher for each pattern, in the same order as the map
+  matchers: Vec<(String, GlobMatcher)>,
 }
 
 impl PipelineMap {
   pub fn new(map: IndexMap<String, Vec<PluginNode>>) -> Self {
-    Self { map }
+    let matchers = map.keys().map(|pattern| pattern_matcher(pattern)).collect();
+
+    Self { map, matchers }
   }
 
   /// Finds pipelines contained by a pattern that match the given file path and named pipeline
@@ -67,16 +75,16 @@ impl PipelineMap {
   /// pipeline_map.get(&PathBuf::from("Cargo.toml"), &None::<String>);
   /// ```
   pub fn get(&self, path: &Path, named_pipeline: &Option<impl AsRef<str>>) -> Vec<PluginNode> {
-    let basename = path.file_name().unwrap().to_str().unwrap();
-    let path = path.as_os_str().to_str().unwrap();
+    let path = normalize_separators(path);
+    let basename = basename(&path);
+    let path = path.as_ref();
     let mut matches: Vec<PluginNode> = Vec::new();
 
     // If a pipeline is requested, a the glob needs to match exactly
     if let Some(pipeline) = named_pipeline {
       let exact_match = self
-        .map
-        .iter()
-        .find(|(pattern, _)| is_match(pattern, path, basename, pipeline.as_ref()));
+        .pipelines()
+        .find(|(matcher, _)| matcher_matches(matcher, path, basename, pipeline.as_ref()));
 
       if let Some((_, pipelines)) = exact_match {
         matches.extend(pipelines.iter().cloned());
@@ -85,8 +93,8 @@ impl PipelineMap {
       }
     }
 
-    for (pattern, pipelines) in self.map.iter() {
-      if is_match(&pattern, path, basename, "") {
+    for (matcher, pipelines) in self.pipelines() {
+      if matcher_matches(matcher, path, basename, "") {
         matches.extend(pipelines.iter().cloned());
       }
     }
@@ -94,6 +102,96 @@ impl PipelineMap {
     matches
   }
 
+  /// Finds the first plugin that [`get`](Self::get) would return for the given path and pipeline
+  ///
+  /// This stops at the first matching pattern that has any plugins, rather than collecting every
+  /// matching pipeline.
+  pub fn get_first(
+    &self,
+    path: &Path,
+    named_pipeline: &Option<impl AsRef<str>>,
+  ) -> Option<PluginNode> {
+    self.find_first(path, named_pipeline).cloned()
+  }
+
+  /// Borrows the plugin that [`get_first`](Self::get_first) returns, without cloning it
+  pub(crate) fn find_first(
+    &self,
+    path: &Path,
+    named_pipeline: &Option<impl AsRef<str>>,
+  ) -> Option<&PluginNode> {
+    let path = normalize_separators(path);
+    let basename = basename(&path);
+    let path = path.as_ref();
+
+    let mut exact_match = None;
+    if let Some(pipeline) = named_pipeline {
+      let (_, pipelines) = self
+        .pipelines()
+        .find(|(matcher, _)| matcher_matches(matcher, path, basename, pipeline.as_ref()))?;
+
+      exact_match = pipelines.first();
+    }
+
+    exact_match
+      .into_iter()
+      .chain(
+        self
+          .pipelines()
+          .filter(|(matcher, _)| matcher_matches(matcher, path, basename, ""))
+          .flat_map(|(_, pipelines)| pipelines.first()),
+      )
+      .next()
+  }
+
+  /// Returns the first pattern matching the path that has an empty pipeline
+  ///
+  /// Patterns are matched in the same way as [`get`](Self::get), so this explains why `get`
+  /// returned no plugins for a path that a pattern does match.
+  pub(crate) fn find_empty_pattern(
+    &self,
+    path: &Path,
+    named_pipeline: &Option<impl AsRef<str>>,
+  ) -> Option<&str> {
+    let path = normalize_separators(path);
+    let basename = basename(&path);
+    let path = path.as_ref();
+    let pipeline = named_pipeline.as_ref().map_or("", |p| p.as_ref());
+
+    self
+      .map
+      .iter()
+      .zip(self.matchers.iter())
+      .find(|((_pattern, plugins), matcher)| {
+        plugins.is_empty() && matcher_matches(matcher, path, basename, pipeline)
+      })
+      .map(|((pattern, _plugins), _matcher)| pattern.as_str())
+  }
+
+  /// Groups the files matched by each pattern, regardless of its named pipeline
+  ///
+  /// Every pattern is included, so patterns that do not match any of the files map to an empty
+  /// list.
+  pub fn match_files<'a>(&self, files: &'a [PathBuf]) -> IndexMap<&str, Vec<&'a Path>> {
+    self
+      .map
+      .keys()
+      .zip(self.matchers.iter())
+      .map(|(pattern, matcher)| {
+        let matched = files
+          .iter()
+          .map(PathBuf::as_path)
+          .filter(|file| {
+            let path = normalize_separators(file);
+            matcher_matches(matcher, &path, basename(&path), &matcher.0)
+          })
+          .collect();
+
+        (pattern.as_str(), matched)
+      })
+      .collect()
+  }
+
   pub fn contains_named_pipeline(&self, pipeline: impl AsRef<str>) -> bool {
     let named_pipeline = format!("{}:", pipeline.as_ref());
 
@@ -110,16 +208,97 @@ impl PipelineMap {
       .filter_map(|glob| glob.split_once(':').map(|g| g.0))
       .collect()
   }
+
+  /// Returns the map with the pipeline of a pattern replaced, or added after the other patterns
+  pub(crate) fn with_pipeline(mut self, pattern: &str, plugins: Vec<PluginNode>) -> Self {
+    self.map.insert(String::from(pattern), plugins);
+    PipelineMap::new(self.map)
+  }
+
+  /// Iterates over each pattern and its pipeline, in config order
+  pub(crate) fn entries(&self) -> impl Iterator<Item = (&String, &Vec<PluginNode>)> {
+    self.map.iter()
+  }
+
+  /// Returns the patterns that were added, removed, or changed in other
+  ///
+  /// Patterns are compared by their normalized globs, in the same way as `==`.
+  pub(crate) fn changed_patterns(&self, other: &PipelineMap) -> Vec<String> {
+    changed_patterns(&self.map, &other.map, normalize_glob)
+  }
+
+  fn pipelines(&self) -> impl Iterator<Item = (&(String, GlobMatcher), &Vec<PluginNode>)> {
+    self.matchers.iter().zip(self.map.values())
+  }
+}
+
+/// Splits a pattern into its named pipeline, which is empty when there is none, and a matcher for
+/// its glob
+pub(crate) fn pattern_matcher(pattern: &str) -> (String, GlobMatcher) {
+  let (pipeline, glob) = pattern.split_once(':').unwrap_or(("", pattern));
+  (String::from(pipeline), GlobMatcher::new(glob))
+}
+
+pub(crate) fn matcher_matches(
+  (pattern_pipeline, matcher): &(String, GlobMatcher),
+  path: &str,
+  basename: &str,
+  pipeline: &str,
+) -> bool {
+  pipeline == pattern_pipeline && (matcher.is_match(basename) || matcher.is_match(path))
+}
+
+/// Compares patterns by their normalized globs, so that reordered brace sets are equal
+impl PartialEq for PipelineMap {
+  fn eq(&self, other: &Self) -> bool {
+    self.map.len() == other.map.len()
+      && self.map.iter().zip(other.map.iter()).all(
+        |((pattern, plugins), (other_pattern, other_plugins))| {
+          plugins == other_plugins && normalize_glob(pattern) == normalize_glob(other_pattern)
+        },
+      )
+  }
+}
+
+/// Serializes only the patterns and plugins, as the matchers are rebuilt when deserializing
+#[cfg(feature = "serde")]
+impl serde::Serialize for PipelineMap {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    self.map.serialize(serializer)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PipelineMap {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    IndexMap::deserialize(deserializer).map(PipelineMap::new)
+  }
+}
+
+/// Returns the path as a string with `/` separators, so that Windows paths match the globs in a
+/// .parcelrc
+///
+/// Backslashes are only separators on Windows, as elsewhere they are valid file name characters.
+/// Paths that are not valid UTF-8 are converted lossily, so they can still match wildcards.
+pub(crate) fn normalize_separators(path: &Path) -> Cow<'_, str> {
+  let path = path.to_string_lossy();
+  if std::path::MAIN_SEPARATOR == '\\' && path.contains('\\') {
+    Cow::Owned(path.replace('\\', "/"))
+  } else {
+    path
+  }
 }
 
-pub(crate) fn is_match(pattern: &str, path: &str, basename: &str, pipeline: &str) -> bool {
-  let (pattern_pipeline, glob) = pattern.split_once(':').unwrap_or(("", pattern));
-  pipeline == pattern_pipeline && (glob_match(glob, basename) || glob_match(glob, path))
+/// Returns the file name of a path with `/` separators, or the whole path when it has none
+pub(crate) fn basename(path: &str) -> &str {
+  Path::new(path)
+    .file_name()
+    .and_then(|name| name.to_str())
+    .unwrap_or(path)
 }
 
 #[cfg(test)]
 mod tests {
-  use std::path::PathBuf;
   use std::rc::Rc;
 
   use super::*;
@@ -259,6 +438,256 @@ mod tests {
         assert_eq!(map.get(&path, &Some("url")), pipelines_two());
       }
     }
+
+    #[test]
+    fn returns_pipelines_for_literal_patterns() {
+      let empty_vec: Vec<PluginNode> = Vec::new();
+      let map = PipelineMap::new(indexmap! {
+        String::from("package.json") => pipelines(),
+        String::from("types:tsconfig.json") => pipelines_two(),
+      });
+
+      for path in paths("package.json") {
+        assert_eq!(map.get(&path, &None::<&str>), pipelines());
+        assert_eq!(map.get(&path, &Some("types")), empty_vec);
+      }
+
+      for path in paths("tsconfig.json") {
+        assert_eq!(map.get(&path, &None::<&str>), empty_vec);
+        assert_eq!(map.get(&path, &Some("types")), pipelines_two());
+      }
+
+      assert_eq!(
+        map.get(&PathBuf::from("other-package.json"), &None::<&str>),
+        empty_vec
+      );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn returns_pipelines_for_paths_with_backslash_separators() {
+      let empty_vec: Vec<PluginNode> = Vec::new();
+      let map = PipelineMap::new(indexmap! {
+        String::from("src/*.js") => pipelines(),
+        String::from("*.ts") => pipelines_two(),
+      });
+
+      assert_eq!(
+        map.get(&PathBuf::from("src\\a.js"), &None::<&str>),
+        pipelines()
+      );
+      assert_eq!(
+        map.get(&PathBuf::from("C:\\project\\src\\a.ts"), &None::<&str>),
+        pipelines_two()
+      );
+      assert_eq!(
+        map.get(&PathBuf::from("lib\\a.js"), &None::<&str>),
+        empty_vec
+      );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn treats_backslashes_as_file_name_characters() {
+      let empty_vec: Vec<PluginNode> = Vec::new();
+      let map = PipelineMap::new(indexmap! {
+        String::from("src/*.js") => pipelines(),
+      });
+
+      assert_eq!(
+        map.get(&PathBuf::from("src\\a.js"), &None::<&str>),
+        empty_vec
+      );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn matches_paths_that_are_not_utf8() {
+      use std::ffi::OsStr;
+      use std::os::unix::ffi::OsStrExt;
+
+      let map = PipelineMap::new(indexmap! {
+        String::from("src/*.js") => pipelines(),
+      });
+
+      assert_eq!(
+        map.get(Path::new(OsStr::from_bytes(b"src/\xff.js")), &None::<&str>),
+        pipelines()
+      );
+    }
+  }
+
+  mod get_first {
+    use indexmap::indexmap;
+
+    use super::*;
+
+    fn assert_matches_get(map: &PipelineMap, path: &str, pipeline: Option<&str>) {
+      let path = PathBuf::from(path);
+
+      assert_eq!(
+        map.get_first(&path, &pipeline),
+        map.get(&path, &pipeline).first().cloned()
+      );
+    }
+
+    #[test]
+    fn returns_none_for_empty_map() {
+      let map = PipelineMap::new(IndexMap::new());
+
+      assert_eq!(map.get_first(&PathBuf::from("a.js"), &None::<&str>), None);
+      assert_eq!(map.get_first(&PathBuf::from("a.js"), &Some("url")), None);
+    }
+
+    #[test]
+    fn returns_head_of_get() {
+      let map = PipelineMap::new(indexmap! {
+        String::from("*.css") => Vec::new(),
+        String::from("*.{js,ts}") => pipelines_three(),
+        String::from("*.toml") => pipelines_two(),
+        String::from("types:*.{ts,tsx}") => pipelines(),
+        String::from("empty:*.ts") => Vec::new(),
+        String::from("url:*") => pipelines_two()
+      });
+
+      for path in ["a.js", "a.ts", "a.tsx", "a.toml", "a.css", "/src/a.ts"] {
+        for pipeline in [
+          None,
+          Some("types"),
+          Some("empty"),
+          Some("url"),
+          Some("other"),
+        ] {
+          assert_matches_get(&map, path, pipeline);
+        }
+      }
+    }
+
+    #[test]
+    fn returns_none_when_no_matching_pipeline() {
+      let map = PipelineMap::new(indexmap! {
+        String::from("*.{js,ts}") => pipelines()
+      });
+
+      assert_eq!(map.get_first(&PathBuf::from("a.js"), &Some("url")), None);
+    }
+  }
+
+  mod match_files {
+    use indexmap::indexmap;
+
+    use super::*;
+
+    #[test]
+    fn groups_files_by_pattern() {
+      let map = PipelineMap::new(indexmap! {
+        String::from("types:*.{ts,tsx}") => pipelines(),
+        String::from("*.{js,ts}") => pipelines_two(),
+        String::from("*.toml") => pipelines_three(),
+        String::from("*.vue") => pipelines(),
+      });
+
+      let files = vec![
+        PathBuf::from("/src/a.ts"),
+        PathBuf::from("/src/b.tsx"),
+        PathBuf::from("/src/c.js"),
+        PathBuf::from("/Cargo.toml"),
+      ];
+
+      assert_eq!(
+        map.match_files(&files),
+        indexmap! {
+          "types:*.{ts,tsx}" => vec![Path::new("/src/a.ts"), Path::new("/src/b.tsx")],
+          "*.{js,ts}" => vec![Path::new("/src/a.ts"), Path::new("/src/c.js")],
+          "*.toml" => vec![Path::new("/Cargo.toml")],
+          "*.vue" => Vec::new(),
+        }
+      );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn groups_files_with_backslash_separators() {
+      let map = PipelineMap::new(indexmap! {
+        String::from("src/*.js") => pipelines(),
+      });
+
+      let files = vec![PathBuf::from("src\\a.js"), PathBuf::from("lib\\b.js")];
+
+      assert_eq!(
+        map.match_files(&files),
+        indexmap! { "src/*.js" => vec![Path::new("src\\a.js")] }
+      );
+    }
+  }
+
+  mod eq {
+    use indexmap::indexmap;
+
+    use super::*;
+
+    #[test]
+    fn equals_maps_with_reordered_brace_sets() {
+      assert_eq!(
+        PipelineMap::new(indexmap! {
+          String::from("types:*.{ts,tsx}") => pipelines(),
+          String::from("*.{js,mjs,cjs}") => pipelines_two(),
+        }),
+        PipelineMap::new(indexmap! {
+          String::from("types:*.{tsx,ts}") => pipelines(),
+          String::from("*.{cjs,js,mjs,js}") => pipelines_two(),
+        })
+      );
+    }
+
+    #[test]
+    fn does_not_equal_maps_with_different_patterns() {
+      assert_ne!(
+        PipelineMap::new(indexmap! {
+          String::from("*.{js,ts}") => pipelines(),
+        }),
+        PipelineMap::new(indexmap! {
+          String::from("*.{js,tsx}") => pipelines(),
+        })
+      );
+
+      assert_ne!(
+        PipelineMap::new(indexmap! {
+          String::from("*.{js,ts}") => pipelines(),
+          String::from("*.css") => pipelines(),
+        }),
+        PipelineMap::new(indexmap! {
+          String::from("*.css") => pipelines(),
+          String::from("*.{ts,js}") => pipelines(),
+        })
+      );
+    }
+  }
+
+  mod matcher_matches {
+    use super::*;
+
+    fn is_match(pattern: &str, path: &str, basename: &str, pipeline: &str) -> bool {
+      matcher_matches(&pattern_matcher(pattern), path, basename, pipeline)
+    }
+
+    #[test]
+    fn matches_when_both_pipelines_are_empty() {
+      assert!(is_match("*.js", "src/a.js", "a.js", ""));
+      assert!(is_match("*.js", "a.js", "a.js", ""));
+    }
+
+    #[test]
+    fn matches_when_pipelines_are_equal() {
+      assert!(is_match("url:*.js", "src/a.js", "a.js", "url"));
+    }
+
+    #[test]
+    fn does_not_match_when_pipelines_differ() {
+      assert!(!is_match("url:*.js", "src/a.js", "a.js", ""));
+      assert!(!is_match("*.js", "src/a.js", "a.js", "url"));
+      assert!(!is_match("types:*.js", "src/a.js", "a.js", "url"));
+    }
   }
 
   mod contains_named_pipeline {
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Creates the in-process implementation of a registered plugin
+pub type PluginFactory<P> = Box<dyn Fn() -> P>;
+
+/// Plugins implemented in-process, keyed by the package name they are configured with
+///
+/// Registered plugins are used instead of resolving their package, which allows embedders to
+/// provide plugins without installing them. The plugin type is left to the embedder, such as a
+/// boxed trait object for each plugin category.
+pub struct PluginRegistry<P> {
+  factories: HashMap<String, PluginFactory<P>>,
+}
+
+impl<P> Default for PluginRegistry<P> {
+  fn default() -> Self {
+    PluginRegistry {
+      factories: HashMap::new(),
+    }
+  }
+}
+
+impl<P> PluginRegistry<P> {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Registers a plugin, replacing any plugin already registered with the same package name
+  pub fn register(&mut self, package_name: &str, factory: impl Fn() -> P + 'static) {
+    self
+      .factories
+      .insert(String::from(package_name), Box::new(factory));
+  }
+
+  pub fn contains(&self, package_name: &str) -> bool {
+    self.factories.contains_key(package_name)
+  }
+
+  /// Creates the plugin registered with the package name, if any
+  pub fn create(&self, package_name: &str) -> Option<P> {
+    self.factories.get(package_name).map(|factory| factory())
+  }
+}
+
+/// A plugin that was either found in a registry, or resolved to the path of its module
+#[derive(Debug, PartialEq)]
+pub enum LoadedPlugin<P> {
+  InProcess(P),
+  Resolved(PathBuf),
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod create {
+    use super::*;
+
+    #[test]
+    fn returns_registered_plugins() {
+      let mut registry = PluginRegistry::new();
+      registry.register("@scope/parcel-transformer-rust", || 1);
+
+      assert!(registry.contains("@scope/parcel-transformer-rust"));
+      assert_eq!(registry.create("@scope/parcel-transformer-rust"), Some(1));
+    }
+
+    #[test]
+    fn returns_none_for_unregistered_plugins() {
+      let registry: PluginRegistry<u32> = PluginRegistry::new();
+
+      assert!(!registry.contains("@parcel/transformer-js"));
+      assert_eq!(registry.create("@parcel/transformer-js"), None);
+    }
+  }
+}
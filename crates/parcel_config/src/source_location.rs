@@ -0,0 +1,455 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::iter::Peekable;
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::Chars;
+
+/// The phases of a .parcelrc that list plugins
+const PHASES: [&str; 10] = [
+  "bundler",
+  "compressors",
+  "namers",
+  "optimizers",
+  "packagers",
+  "reporters",
+  "resolvers",
+  "runtimes",
+  "transformers",
+  "validators",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Location {
+  pub line: u32,
+  pub column: u32,
+}
+
+/// Identifies a specific location in a .parcelrc
+///
+/// Source locations start at 1:1, and the end is the last character of the span.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceLocation {
+  /// The file path associated with the source
+  pub file_path: PathBuf,
+
+  /// The starting position within the source code
+  pub start: Location,
+
+  /// The final location in the source code
+  pub end: Location,
+}
+
+impl fmt::Display for SourceLocation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{}:{}:{}",
+      self.file_path.display(),
+      self.start.line,
+      self.start.column
+    )
+  }
+}
+
+/// Identifies a plugin entry by the phase, pattern, and index it is listed at
+///
+/// Phases that take a single plugin, such as the bundler or the packager for a pattern, use an
+/// index of 0.
+///
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PluginKey {
+  pub phase: String,
+  pub pattern: Option<String>,
+  pub index: usize,
+}
+
+/// The locations of the plugin entries within a .parcelrc
+///
+/// The JSON5 parser does not report positions, so they are found by scanning the source again.
+///
+#[derive(Debug, Default)]
+pub struct PluginLocations(HashMap<PluginKey, SourceLocation>);
+
+impl PluginLocations {
+  /// Scans a .parcelrc for its plugin entries
+  ///
+  /// Entries after any syntax the scanner does not understand are not located, as the source is
+  /// expected to have already been parsed successfully.
+  ///
+  pub fn from_source(file_path: &Path, source: &str) -> Self {
+    let mut scanner = Scanner {
+      chars: source.chars().peekable(),
+      file_path,
+      location: Location { line: 1, column: 1 },
+      locations: HashMap::new(),
+    };
+
+    scanner.value(&mut Vec::new());
+
+    PluginLocations(scanner.locations)
+  }
+
+  pub fn get(&self, key: &PluginKey) -> Option<&SourceLocation> {
+    self.0.get(key)
+  }
+}
+
+/// A step in the path from the root of the document to a value
+enum Segment {
+  Key(String),
+  Index(usize),
+}
+
+/// Returns the plugin entry that a string value at path is the name of, if any
+///
+/// Plugins are listed directly by name, or with the object form `{ "plugin": "name" }`.
+///
+fn plugin_key(path: &[Segment]) -> Option<PluginKey> {
+  let (phase, rest) = match path.split_first()? {
+    (Segment::Key(phase), rest) if PHASES.contains(&phase.as_str()) => (phase, rest),
+    _ => return None,
+  };
+
+  let rest = match rest {
+    [entry @ .., Segment::Key(key)] if key == "plugin" => match entry.last() {
+      Some(Segment::Index(_)) => entry,
+      _ => return None,
+    },
+    rest => rest,
+  };
+
+  let (pattern, index) = match rest {
+    [] => (None, 0),
+    [Segment::Index(index)] => (None, *index),
+    [Segment::Key(pattern)] => (Some(pattern.clone()), 0),
+    [Segment::Key(pattern), Segment::Index(index)] => (Some(pattern.clone()), *index),
+    _ => return None,
+  };
+
+  Some(PluginKey {
+    phase: phase.clone(),
+    pattern,
+    index,
+  })
+}
+
+struct Scanner<'a> {
+  chars: Peekable<Chars<'a>>,
+  file_path: &'a Path,
+  location: Location,
+  locations: HashMap<PluginKey, SourceLocation>,
+}
+
+impl<'a> Scanner<'a> {
+  fn peek(&mut self) -> Option<char> {
+    self.chars.peek().copied()
+  }
+
+  fn next(&mut self) -> Option<char> {
+    let c = self.chars.next()?;
+    if c == '\n' {
+      self.location.line += 1;
+      self.location.column = 1;
+    } else {
+      self.location.column += 1;
+    }
+
+    Some(c)
+  }
+
+  /// Skips whitespace and comments
+  fn skip_trivia(&mut self) {
+    loop {
+      match self.peek() {
+        Some(c) if c.is_whitespace() => {
+          self.next();
+        }
+        Some('/') => {
+          let mut lookahead = self.chars.clone();
+          lookahead.next();
+          match lookahead.next() {
+            Some('/') => while self.next().is_some_and(|c| c != '\n') {},
+            Some('*') => {
+              self.next();
+              self.next();
+              let mut previous = None;
+              while let Some(c) = self.next() {
+                if previous == Some('*') && c == '/' {
+                  break;
+                }
+                previous = Some(c);
+              }
+            }
+            _ => return,
+          }
+        }
+        _ => return,
+      }
+    }
+  }
+
+  /// Reads a quoted string, returning its value and the location of the closing quote
+  fn string(&mut self) -> Option<(String, Location)> {
+    let quote = self.next()?;
+    let mut value = String::new();
+    loop {
+      let location = self.location;
+      match self.next()? {
+        c if c == quote => return Some((value, location)),
+        '\\' => self.escape(&mut value)?,
+        c => value.push(c),
+      }
+    }
+  }
+
+  /// Reads the escape sequence after a backslash within a string, pushing the character it
+  /// represents onto value
+  fn escape(&mut self, value: &mut String) -> Option<()> {
+    let c = match self.next()? {
+      'b' => '\u{8}',
+      'f' => '\u{c}',
+      'n' => '\n',
+      'r' => '\r',
+      't' => '\t',
+      'v' => '\u{b}',
+      '0' => '\0',
+      'x' => char::from_u32(self.hex(2)?)?,
+      'u' => {
+        let unit = self.hex(4)?;
+        if (0xd800..0xdc00).contains(&unit) {
+          // A high surrogate is followed by the escaped low surrogate of the pair
+          if self.next()? != '\\' || self.next()? != 'u' {
+            return None;
+          }
+
+          let low = self.hex(4)?;
+          char::from_u32(0x10000 + ((unit - 0xd800) << 10) + low.checked_sub(0xdc00)?)?
+        } else {
+          char::from_u32(unit)?
+        }
+      }
+      // Escaped line terminators continue the string on the next line
+      '\r' => {
+        if self.peek() == Some('\n') {
+          self.next();
+        }
+
+        return Some(());
+      }
+      '\n' | '\u{2028}' | '\u{2029}' => return Some(()),
+      c => c,
+    };
+
+    value.push(c);
+
+    Some(())
+  }
+
+  /// Reads a number of hexadecimal digits
+  fn hex(&mut self, digits: usize) -> Option<u32> {
+    (0..digits).try_fold(0, |value, _digit| {
+      Some(value * 16 + self.next()?.to_digit(16)?)
+    })
+  }
+
+  /// Reads an unquoted key, or a literal such as a number or boolean
+  fn word(&mut self) -> Option<String> {
+    let mut word = String::new();
+    while let Some(c) = self.peek() {
+      if c.is_whitespace() || matches!(c, ',' | ':' | '[' | ']' | '{' | '}' | '/') {
+        break;
+      }
+
+      word.push(c);
+      self.next();
+    }
+
+    (!word.is_empty()).then_some(word)
+  }
+
+  fn value(&mut self, path: &mut Vec<Segment>) -> Option<()> {
+    self.skip_trivia();
+    match self.peek()? {
+      '{' => {
+        self.next();
+        loop {
+          self.skip_trivia();
+          match self.peek()? {
+            '}' => {
+              self.next();
+              return Some(());
+            }
+            ',' => {
+              self.next();
+              continue;
+            }
+            '"' | '\'' => {
+              let (key, _end) = self.string()?;
+              path.push(Segment::Key(key));
+            }
+            _ => {
+              let key = self.word()?;
+              path.push(Segment::Key(key));
+            }
+          }
+
+          self.skip_trivia();
+          if self.next()? != ':' {
+            return None;
+          }
+
+          self.value(path)?;
+          path.pop();
+        }
+      }
+      '[' => {
+        self.next();
+        let mut index = 0;
+        loop {
+          self.skip_trivia();
+          match self.peek()? {
+            ']' => {
+              self.next();
+              return Some(());
+            }
+            ',' => {
+              self.next();
+              continue;
+            }
+            _ => {}
+          }
+
+          path.push(Segment::Index(index));
+          self.value(path)?;
+          path.pop();
+          index += 1;
+        }
+      }
+      '"' | '\'' => {
+        let start = self.location;
+        let (_value, end) = self.string()?;
+        if let Some(key) = plugin_key(path) {
+          self.locations.insert(
+            key,
+            SourceLocation {
+              file_path: PathBuf::from(self.file_path),
+              start,
+              end,
+            },
+          );
+        }
+
+        Some(())
+      }
+      _ => self.word().map(|_word| ()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  mod from_source {
+    use super::*;
+
+    fn location(start: (u32, u32), end: (u32, u32)) -> Option<SourceLocation> {
+      Some(SourceLocation {
+        file_path: PathBuf::from("/.parcelrc"),
+        start: Location {
+          line: start.0,
+          column: start.1,
+        },
+        end: Location {
+          line: end.0,
+          column: end.1,
+        },
+      })
+    }
+
+    fn key(phase: &str, pattern: Option<&str>, index: usize) -> PluginKey {
+      PluginKey {
+        phase: String::from(phase),
+        pattern: pattern.map(String::from),
+        index,
+      }
+    }
+
+    #[test]
+    fn locates_plugin_entries() {
+      let source = r#"{
+  // Comments are skipped
+  extends: "@parcel/config-default",
+  "bundler": "@parcel/bundler-default",
+  "transformers": {
+    "*.{js,ts}": ["...", '@parcel/transformer-js'],
+    /* Plugins can be disabled */
+    "*.css": [{ "plugin": "@parcel/transformer-css", "disabled": true }, false]
+  },
+  "packagers": { "*.js": "@parcel/packager-js" },
+  "reporters": ["@parcel/reporter-cli",],
+}"#;
+
+      let locations = PluginLocations::from_source(Path::new("/.parcelrc"), source);
+
+      assert_eq!(
+        locations.get(&key("bundler", None, 0)).cloned(),
+        location((4, 14), (4, 38))
+      );
+      assert_eq!(
+        locations
+          .get(&key("transformers", Some("*.{js,ts}"), 0))
+          .cloned(),
+        location((6, 19), (6, 23))
+      );
+      assert_eq!(
+        locations
+          .get(&key("transformers", Some("*.{js,ts}"), 1))
+          .cloned(),
+        location((6, 26), (6, 49))
+      );
+      assert_eq!(
+        locations
+          .get(&key("transformers", Some("*.css"), 0))
+          .cloned(),
+        location((8, 27), (8, 51))
+      );
+      assert_eq!(
+        locations.get(&key("packagers", Some("*.js"), 0)).cloned(),
+        location((10, 26), (10, 46))
+      );
+      assert_eq!(
+        locations.get(&key("reporters", None, 0)).cloned(),
+        location((11, 17), (11, 38))
+      );
+      assert_eq!(locations.get(&key("extends", None, 0)), None);
+    }
+
+    #[test]
+    fn unescapes_keys() {
+      let source = r#"{
+  "transformers": {
+    "*.\u0061\x62\"c\"": ["@parcel/transformer-abc"],
+    "*.\uD83D\uDE00": ["@parcel/transformer-emoji"]
+  }
+}"#;
+
+      let locations = PluginLocations::from_source(Path::new("/.parcelrc"), source);
+
+      assert_eq!(
+        locations
+          .get(&key("transformers", Some("*.ab\"c\""), 0))
+          .cloned(),
+        location((3, 27), (3, 51))
+      );
+      assert_eq!(
+        locations
+          .get(&key("transformers", Some("*.\u{1f600}"), 0))
+          .cloned(),
+        location((4, 24), (4, 50))
+      );
+    }
+  }
+}
@@ -3,9 +3,11 @@
 //! Effectively when we get an "entry"; we try to find it as a file, project
 //! directory or glob, then resolve files
 use std::path::Path;
+use std::path::PathBuf;
 
 use anyhow::anyhow;
 use napi_derive::napi;
+use parcel_filesystem::FileType;
 use parcel_resolver::FileSystem;
 
 use crate::project_path::ProjectPath;
@@ -37,7 +39,7 @@ fn merge_results(target: &mut EntryResult, source: EntryResult) {
 ///
 /// TODO: This is not a sufficient implementation
 fn is_glob(path: &Path) -> bool {
-  path.to_str().unwrap().contains("*")
+  path.to_string_lossy().contains(['*', '?', '['])
 }
 
 /// Params object for resolve functions
@@ -48,6 +50,9 @@ struct ResolveEntryParams<'a, FS: FileSystem> {
 }
 
 /// Resolve an entry-point
+///
+/// Paths that exist are resolved as written, so that file names containing glob characters such
+/// as `src/[slug].html` are not expanded. Only paths that do not exist are treated as globs.
 fn resolve_entry(
   ResolveEntryParams {
     path,
@@ -55,26 +60,28 @@ fn resolve_entry(
     project_root,
   }: ResolveEntryParams<impl FileSystem>,
 ) -> anyhow::Result<EntryResult> {
-  if is_glob(path) {
-    resolve_entry_glob(ResolveEntryParams {
+  match fs.file_type(path) {
+    Some(FileType::File) => resolve_entry_file(ResolveEntryParams {
       path,
       fs,
       project_root,
-    })
-  } else if fs.is_file(path) {
-    resolve_entry_file(ResolveEntryParams {
+    }),
+    Some(FileType::Dir) => todo!("directory entries are not implemented"),
+    None if is_glob(path) => resolve_entry_glob(ResolveEntryParams {
       path,
       fs,
       project_root,
-    })
-  } else if fs.is_dir(path) {
-    todo!("directory entries are not implemented")
-  } else {
-    Err(anyhow!("[napi] Invalid entry, file not found"))
+    }),
+    None => Err(anyhow!("[napi] Invalid entry, file not found")),
   }
 }
 
 /// Resolve an entry-point that is a glob by expanding the glob then resolving each of its matches.
+///
+/// Matches are found by walking the directory the glob starts from through `fs`, and the glob is
+/// returned so that creating new matching files invalidates the request. Relative globs are
+/// matched from the current working directory, and subdirectories are only walked as deep as the
+/// glob can match, or entirely when it contains `**`.
 fn resolve_entry_glob(
   ResolveEntryParams {
     path,
@@ -82,11 +89,70 @@ fn resolve_entry_glob(
     project_root,
   }: ResolveEntryParams<impl FileSystem>,
 ) -> anyhow::Result<EntryResult> {
-  let pattern = path.to_str().unwrap();
-  let results = glob::glob(pattern)?;
-  let mut result = EntryResult::default();
-  for path in results {
-    let path = path?;
+  let pattern = path.to_string_lossy();
+  let absolute_pattern = if path.is_absolute() {
+    pattern.to_string()
+  } else {
+    let cwd = fs.cwd()?;
+    let cwd = glob::Pattern::escape(&cwd.to_string_lossy());
+    Path::new(&cwd).join(path).to_string_lossy().into_owned()
+  };
+
+  let glob = glob::Pattern::new(&absolute_pattern)?;
+  let match_options = glob::MatchOptions {
+    require_literal_separator: true,
+    ..Default::default()
+  };
+
+  let base_components = path
+    .components()
+    .take_while(|component| !is_glob(Path::new(component.as_os_str())))
+    .count();
+
+  let base_dir: PathBuf = path.components().take(base_components).collect();
+  let base_dir = fs.cwd()?.join(base_dir);
+
+  // The number of directories below the base directory that the glob can match within
+  let max_depth = if pattern.contains("**") {
+    None
+  } else {
+    Some(path.components().count() - base_components - 1)
+  };
+
+  let mut matches = Vec::new();
+  let mut dirs = vec![(base_dir, 0)];
+  while let Some((dir, depth)) = dirs.pop() {
+    let entries = match fs.read_dir(&dir) {
+      Ok(entries) => entries,
+      Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
+      Err(error) => return Err(error.into()),
+    };
+
+    for entry in entries {
+      match fs.file_type(&entry) {
+        Some(FileType::Dir) if max_depth.map_or(true, |max_depth| depth < max_depth) => {
+          dirs.push((entry, depth + 1))
+        }
+        Some(FileType::File) if glob.matches_path_with(&entry, match_options) => {
+          matches.push(entry)
+        }
+        _ => {}
+      }
+    }
+  }
+
+  if matches.is_empty() {
+    return Err(anyhow!("[napi] Invalid entry, no files match {}", pattern));
+  }
+
+  matches.sort();
+
+  let mut result = EntryResult {
+    globs: vec![pattern.to_string()],
+    ..Default::default()
+  };
+
+  for path in matches {
     merge_results(
       &mut result,
       resolve_entry(ResolveEntryParams {
@@ -96,6 +162,7 @@ fn resolve_entry_glob(
       })?,
     );
   }
+
   Ok(result)
 }
 
@@ -235,4 +302,137 @@ mod test {
       }
     );
   }
+
+  #[test]
+  fn test_resolve_entry_glob() {
+    let mut fs = InMemoryFileSystem::default();
+    fs.set_current_working_directory("/project".into());
+    fs.write_file("/project/src/b.html", "");
+    fs.write_file("/project/src/a.html", "");
+    fs.write_file("/project/src/index.css", "");
+    fs.write_file("/project/src/nested/c.html", "");
+
+    let result = resolve_entry(ResolveEntryParams {
+      path: Path::new("/project/src/*.html"),
+      fs: &fs,
+      project_root: Path::new("/project"),
+    });
+
+    assert_eq!(
+      result.unwrap(),
+      EntryResult {
+        entries: vec![
+          Entry {
+            file_path: ProjectPath::from("/project/src/a.html"),
+            package_path: ProjectPath::from("/project"),
+          },
+          Entry {
+            file_path: ProjectPath::from("/project/src/b.html"),
+            package_path: ProjectPath::from("/project"),
+          },
+        ],
+        globs: vec![String::from("/project/src/*.html")],
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn test_resolve_entry_relative_glob() {
+    let mut fs = InMemoryFileSystem::default();
+    fs.set_current_working_directory("/project".into());
+    fs.create_directory("/project/src");
+    fs.write_file("/project/index.html", "");
+    fs.write_file("/project/src/about.html", "");
+
+    let result = resolve_entry(ResolveEntryParams {
+      path: Path::new("*.html"),
+      fs: &fs,
+      project_root: Path::new("/project"),
+    });
+
+    assert_eq!(
+      result.unwrap(),
+      EntryResult {
+        entries: vec![Entry {
+          file_path: ProjectPath::from("/project/index.html"),
+          package_path: ProjectPath::from("/project"),
+        }],
+        globs: vec![String::from("*.html")],
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn test_resolve_entry_recursive_glob() {
+    let mut fs = InMemoryFileSystem::default();
+    fs.set_current_working_directory("/project".into());
+    fs.create_directory("/project/src/nested");
+    fs.write_file("/project/src/a.html", "");
+    fs.write_file("/project/src/nested/b.html", "");
+
+    let result = resolve_entry(ResolveEntryParams {
+      path: Path::new("src/**/*.html"),
+      fs: &fs,
+      project_root: Path::new("/project"),
+    });
+
+    assert_eq!(
+      result.unwrap().entries,
+      vec![
+        Entry {
+          file_path: ProjectPath::from("/project/src/a.html"),
+          package_path: ProjectPath::from("/project"),
+        },
+        Entry {
+          file_path: ProjectPath::from("/project/src/nested/b.html"),
+          package_path: ProjectPath::from("/project"),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_resolve_entry_literal_path_with_glob_characters() {
+    let mut fs = InMemoryFileSystem::default();
+    fs.set_current_working_directory("/project".into());
+    fs.write_file("/project/src/[slug].html", "");
+    fs.write_file("/project/src/s.html", "");
+
+    let result = resolve_entry(ResolveEntryParams {
+      path: Path::new("/project/src/[slug].html"),
+      fs: &fs,
+      project_root: Path::new("/project"),
+    });
+
+    assert_eq!(
+      result.unwrap(),
+      EntryResult {
+        entries: vec![Entry {
+          file_path: ProjectPath::from("/project/src/[slug].html"),
+          package_path: ProjectPath::from("/project"),
+        }],
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn test_resolve_entry_glob_without_matches() {
+    let mut fs = InMemoryFileSystem::default();
+    fs.set_current_working_directory("/project".into());
+    fs.write_file("/project/src/index.css", "");
+
+    let result = resolve_entry(ResolveEntryParams {
+      path: Path::new("/project/src/*.html"),
+      fs: &fs,
+      project_root: Path::new("/project"),
+    });
+
+    assert_eq!(
+      result.unwrap_err().to_string(),
+      "[napi] Invalid entry, no files match /project/src/*.html"
+    );
+  }
 }
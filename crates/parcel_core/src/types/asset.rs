@@ -3,9 +3,9 @@ use std::hash::Hasher;
 use std::num::NonZeroU32;
 use std::path::PathBuf;
 
-use ahash::AHasher;
 use serde::Deserialize;
 use serde::Serialize;
+use xxhash_rust::xxh3::Xxh3;
 
 use super::bundle::BundleBehavior;
 use super::environment::Environment;
@@ -16,6 +16,18 @@ use super::symbol::Symbol;
 #[derive(PartialEq, Hash, Clone, Copy, Debug)]
 pub struct AssetId(pub NonZeroU32);
 
+/// The leading bytes of binary file types, with the extension of each type
+const SIGNATURES: [(&[u8], &str); 8] = [
+  (b"\x89PNG\r\n\x1a\n", "png"),
+  (b"\xff\xd8\xff", "jpg"),
+  (b"GIF87a", "gif"),
+  (b"GIF89a", "gif"),
+  (b"\0asm", "wasm"),
+  (b"%PDF-", "pdf"),
+  (b"wOFF", "woff"),
+  (b"wOF2", "woff2"),
+];
+
 /// An asset is a file or part of a file that may represent any data type including source code, binary data, etc.
 ///
 /// Note that assets may exist in the file system or virtually.
@@ -30,6 +42,14 @@ pub struct Asset {
   /// Controls which bundle the asset is placed into
   pub bundle_behavior: BundleBehavior,
 
+  /// The contents of the asset, when they have been read
+  ///
+  /// Contents are stored separately from the asset when it is cached or sent to workers, so they
+  /// are not serialized.
+  ///
+  #[serde(skip)]
+  pub contents: Vec<u8>,
+
   /// The environment of the asset
   pub env: Environment,
 
@@ -80,18 +100,55 @@ pub struct Asset {
 }
 
 impl Asset {
+  /// Returns an id that identifies the same logical asset across builds
+  ///
+  /// The id is a stable hash of the file path, environment, pipeline, query and unique key, so it
+  /// can be used in cache keys for incremental builds. Fields that change during transformation,
+  /// such as the asset type, contents, and metadata, are not included.
+  ///
   pub fn id(&self) -> u64 {
-    let mut hasher = AHasher::default();
+    let mut hasher = Xxh3::default();
 
-    self.asset_type.hash(&mut hasher);
-    self.env.hash(&mut hasher);
     self.file_path.hash(&mut hasher);
+    self.env.stable_hash().hash(&mut hasher);
     self.pipeline.hash(&mut hasher);
     self.query.hash(&mut hasher);
     self.unique_key.hash(&mut hasher);
 
     hasher.finish()
   }
+
+  /// Infers the file type of the asset, for assets whose file path may have no extension
+  ///
+  /// The extension of the file path is used when there is one. Otherwise, a pipeline named after
+  /// a file type, such as `ts:`, is used as a hint, before detecting binary formats from the
+  /// leading bytes of the contents.
+  ///
+  pub fn infer_type(&self) -> Option<&str> {
+    if let Some(extension) = self.file_path.extension().and_then(|e| e.to_str()) {
+      return Some(extension);
+    }
+
+    let is_signature_type = |pipeline: &str| SIGNATURES.iter().any(|(_, ext)| *ext == pipeline);
+    let pipeline_type = self.pipeline.as_deref().filter(|pipeline| {
+      !matches!(FileType::from_extension(pipeline), FileType::Other(_))
+        || is_signature_type(pipeline)
+    });
+
+    if pipeline_type.is_some() {
+      return pipeline_type;
+    }
+
+    let contents = &self.contents;
+    if contents.len() >= 12 && contents.starts_with(b"RIFF") && &contents[8..12] == b"WEBP" {
+      return Some("webp");
+    }
+
+    SIGNATURES
+      .iter()
+      .find(|(signature, _)| contents.starts_with(signature))
+      .map(|(_, extension)| *extension)
+  }
 }
 
 /// Statistics that pertain to an asset
@@ -100,3 +157,141 @@ pub struct AssetStats {
   pub size: u32,
   pub time: u32,
 }
+
+#[cfg(test)]
+mod tests {
+  use parcel_resolver::IncludeNodeModules;
+
+  use super::*;
+  use crate::types::environment::engines::Engines;
+  use crate::types::environment::EnvironmentContext;
+  use crate::types::environment::OutputFormat;
+  use crate::types::environment::SourceType;
+
+  fn asset() -> Asset {
+    Asset {
+      asset_type: FileType::Js,
+      bundle_behavior: BundleBehavior::None,
+      contents: Vec::new(),
+      env: Environment {
+        context: EnvironmentContext::Browser,
+        engines: Engines::default(),
+        include_node_modules: IncludeNodeModules::default(),
+        is_library: false,
+        loc: None,
+        output_format: OutputFormat::EsModule,
+        should_scope_hoist: false,
+        should_optimize: false,
+        source_map: None,
+        source_type: SourceType::Module,
+      },
+      file_path: PathBuf::from("/project/src/index.js"),
+      is_bundle_splittable: true,
+      is_source: true,
+      meta: JSONObject::new(),
+      pipeline: None,
+      query: None,
+      side_effects: true,
+      stats: AssetStats::default(),
+      symbols: Vec::new(),
+      unique_key: None,
+    }
+  }
+
+  mod infer_type {
+    use super::*;
+
+    fn extensionless_asset(contents: &[u8]) -> Asset {
+      Asset {
+        contents: contents.to_vec(),
+        file_path: PathBuf::from("/project/src/image"),
+        ..asset()
+      }
+    }
+
+    #[test]
+    fn returns_the_file_path_extension() {
+      let asset = Asset {
+        contents: b"\x89PNG\r\n\x1a\n".to_vec(),
+        ..asset()
+      };
+
+      assert_eq!(asset.infer_type(), Some("js"));
+    }
+
+    #[test]
+    fn infers_types_from_magic_bytes() {
+      assert_eq!(
+        extensionless_asset(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").infer_type(),
+        Some("png")
+      );
+      assert_eq!(
+        extensionless_asset(b"RIFF\0\0\0\0WEBPVP8 ").infer_type(),
+        Some("webp")
+      );
+      assert_eq!(
+        extensionless_asset(b"console.log('Hello world');").infer_type(),
+        None
+      );
+    }
+
+    #[test]
+    fn infers_types_from_the_pipeline() {
+      let asset = Asset {
+        pipeline: Some(String::from("ts")),
+        ..extensionless_asset(b"export const a: number = 1;")
+      };
+
+      assert_eq!(asset.infer_type(), Some("ts"));
+
+      let asset = Asset {
+        pipeline: Some(String::from("url")),
+        ..extensionless_asset(b"GIF89a")
+      };
+
+      assert_eq!(asset.infer_type(), Some("gif"));
+    }
+  }
+
+  mod id {
+    use super::*;
+
+    #[test]
+    fn returns_equal_ids_for_the_same_asset() {
+      let transformed = Asset {
+        asset_type: FileType::Css,
+        stats: AssetStats { size: 10, time: 5 },
+        ..asset()
+      };
+
+      assert_eq!(asset().id(), asset().id());
+      assert_eq!(transformed.id(), asset().id());
+    }
+
+    #[test]
+    fn returns_different_ids_for_different_pipelines() {
+      let inline = Asset {
+        pipeline: Some(String::from("inline")),
+        ..asset()
+      };
+
+      assert_ne!(inline.id(), asset().id());
+    }
+
+    #[test]
+    fn returns_different_ids_for_different_queries() {
+      let small = Asset {
+        query: Some(String::from("width=200")),
+        ..asset()
+      };
+
+      let large = Asset {
+        query: Some(String::from("width=800")),
+        ..asset()
+      };
+
+      assert_ne!(small.id(), large.id());
+      assert_ne!(small.id(), asset().id());
+    }
+  }
+}
@@ -3,14 +3,17 @@ use std::hash::Hasher;
 use std::path::PathBuf;
 
 use ahash::AHasher;
+use indexmap::IndexMap;
 use parcel_resolver::ExportsCondition;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_repr::Deserialize_repr;
 use serde_repr::Serialize_repr;
 
+use super::asset::Asset;
 use super::bundle::BundleBehavior;
 use super::environment::Environment;
+use super::environment::EnvironmentOverrides;
 use super::json::JSONObject;
 use super::source::SourceLocation;
 use super::symbol::Symbol;
@@ -70,6 +73,14 @@ pub struct Dependency {
   /// Determines when the dependency should be loaded
   pub priority: Priority,
 
+  /// The query parameters of the specifier, such as `width=200` in `./img.png?width=200`
+  ///
+  /// Parameters are transformer options, so the same file imported with different queries is
+  /// transformed into distinct assets.
+  ///
+  #[serde(default)]
+  pub query: Option<IndexMap<String, String>>,
+
   /// The semver version range expected for the dependency
   pub range: Option<String>,
 
@@ -112,6 +123,7 @@ impl Dependency {
       package_conditions: ExportsCondition::empty(),
       pipeline: None,
       priority: Priority::default(),
+      query: parse_query(&specifier),
       range: None,
       resolve_from: None,
       source_asset_id: None,
@@ -123,6 +135,18 @@ impl Dependency {
     }
   }
 
+  /// Creates a dependency found in an asset, inheriting the asset's environment
+  ///
+  /// The overrides replace parts of the inherited environment, such as the context of a worker
+  /// imported with `{ with: { type: 'worker' } }`.
+  ///
+  pub fn from_asset(specifier: String, asset: &Asset, env: EnvironmentOverrides) -> Dependency {
+    Dependency {
+      source_path: Some(asset.file_path.clone()),
+      ..Dependency::new(specifier, asset.env.merge(env))
+    }
+  }
+
   pub fn id(&self) -> u64 {
     // Compute hashed dependency id
     let mut hasher = AHasher::default();
@@ -132,6 +156,11 @@ impl Dependency {
     self.package_conditions.hash(&mut hasher);
     self.pipeline.hash(&mut hasher);
     self.priority.hash(&mut hasher);
+    if let Some(query) = &self.query {
+      for param in query {
+        param.hash(&mut hasher);
+      }
+    }
     self.source_path.hash(&mut hasher);
     self.specifier.hash(&mut hasher);
     self.specifier_type.hash(&mut hasher);
@@ -140,6 +169,25 @@ impl Dependency {
   }
 }
 
+/// Parses the query parameters that follow the `?` in a specifier
+///
+/// Parameters without a value, such as `inline` in `./icon.svg?inline`, have an empty value.
+///
+fn parse_query(specifier: &str) -> Option<IndexMap<String, String>> {
+  let (_path, query) = specifier.split_once('?')?;
+
+  Some(
+    query
+      .split('&')
+      .filter(|param| !param.is_empty())
+      .map(|param| {
+        let (key, value) = param.split_once('=').unwrap_or((param, ""));
+        (String::from(key), String::from(value))
+      })
+      .collect(),
+  )
+}
+
 #[derive(Clone, Debug, Deserialize, Hash, Serialize)]
 pub struct ImportAttribute {
   pub key: String,
@@ -197,3 +245,131 @@ impl Default for SpecifierType {
     SpecifierType::Esm
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use parcel_resolver::IncludeNodeModules;
+
+  use super::*;
+  use crate::types::environment::engines::Engines;
+  use crate::types::environment::EnvironmentContext;
+  use crate::types::environment::OutputFormat;
+  use crate::types::environment::SourceType;
+
+  fn env() -> Environment {
+    Environment {
+      context: EnvironmentContext::Browser,
+      engines: Engines::default(),
+      include_node_modules: IncludeNodeModules::default(),
+      is_library: false,
+      loc: None,
+      output_format: OutputFormat::EsModule,
+      should_scope_hoist: false,
+      should_optimize: false,
+      source_map: None,
+      source_type: SourceType::Module,
+    }
+  }
+
+  mod new {
+    use super::*;
+
+    #[test]
+    fn parses_the_specifier_query() {
+      let dependency = Dependency::new(
+        String::from("./img.png?width=200&format=webp&lossless"),
+        env(),
+      );
+
+      let mut query = IndexMap::new();
+      query.insert(String::from("width"), String::from("200"));
+      query.insert(String::from("format"), String::from("webp"));
+      query.insert(String::from("lossless"), String::new());
+
+      assert_eq!(dependency.query, Some(query));
+    }
+
+    #[test]
+    fn has_no_query_without_a_question_mark() {
+      let dependency = Dependency::new(String::from("./img.png"), env());
+
+      assert_eq!(dependency.query, None);
+    }
+  }
+
+  mod from_asset {
+    use std::num::NonZeroU16;
+
+    use super::*;
+    use crate::types::asset::AssetStats;
+    use crate::types::environment::version::Version;
+    use crate::types::file_type::FileType;
+
+    fn asset() -> Asset {
+      Asset {
+        asset_type: FileType::Js,
+        bundle_behavior: BundleBehavior::None,
+        contents: Vec::new(),
+        env: Environment {
+          engines: Engines {
+            node: Some(Version::new(NonZeroU16::new(18).unwrap(), 0)),
+            ..Engines::default()
+          },
+          ..env()
+        },
+        file_path: PathBuf::from("/project/src/index.js"),
+        is_bundle_splittable: true,
+        is_source: true,
+        meta: JSONObject::new(),
+        pipeline: None,
+        query: None,
+        side_effects: true,
+        stats: AssetStats::default(),
+        symbols: Vec::new(),
+        unique_key: None,
+      }
+    }
+
+    #[test]
+    fn inherits_the_asset_environment() {
+      let dependency = Dependency::from_asset(
+        String::from("./a.js"),
+        &asset(),
+        EnvironmentOverrides::default(),
+      );
+
+      assert_eq!(dependency.env, asset().env);
+      assert_eq!(
+        dependency.source_path,
+        Some(PathBuf::from("/project/src/index.js"))
+      );
+    }
+
+    #[test]
+    fn overrides_the_context_and_preserves_engines() {
+      let dependency = Dependency::from_asset(
+        String::from("./worker.js"),
+        &asset(),
+        EnvironmentOverrides {
+          context: Some(EnvironmentContext::WebWorker),
+          ..EnvironmentOverrides::default()
+        },
+      );
+
+      assert_eq!(dependency.env.context, EnvironmentContext::WebWorker);
+      assert_eq!(dependency.env.engines, asset().env.engines);
+    }
+  }
+
+  mod id {
+    use super::*;
+
+    #[test]
+    fn returns_different_ids_for_different_queries() {
+      let small = Dependency::new(String::from("./img.png?width=200"), env());
+      let large = Dependency::new(String::from("./img.png?width=800"), env());
+
+      assert_ne!(small.id(), large.id());
+    }
+  }
+}
@@ -1,3 +1,5 @@
+use std::hash::Hash;
+use std::hash::Hasher;
 use std::num::NonZeroU32;
 
 use parcel_resolver::IncludeNodeModules;
@@ -5,6 +7,7 @@ use serde::Deserialize;
 use serde::Serialize;
 use serde_repr::Deserialize_repr;
 use serde_repr::Serialize_repr;
+use xxhash_rust::xxh3::Xxh3;
 
 use self::engines::Engines;
 use super::source::SourceLocation;
@@ -68,8 +71,60 @@ pub struct Environment {
   pub source_type: SourceType,
 }
 
-impl std::hash::Hash for Environment {
-  fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+impl Environment {
+  /// Creates a copy of the environment with the given overrides applied
+  ///
+  /// This allows a dependency to change part of its parent environment, such as the context of a
+  /// worker, while inheriting everything else.
+  ///
+  pub fn merge(&self, overrides: EnvironmentOverrides) -> Environment {
+    Environment {
+      context: overrides.context.unwrap_or(self.context),
+      engines: overrides.engines.unwrap_or_else(|| self.engines.clone()),
+      include_node_modules: overrides
+        .include_node_modules
+        .unwrap_or_else(|| self.include_node_modules.clone()),
+      is_library: overrides.is_library.unwrap_or(self.is_library),
+      loc: overrides.loc.or_else(|| self.loc.clone()),
+      output_format: overrides.output_format.unwrap_or(self.output_format),
+      should_scope_hoist: overrides
+        .should_scope_hoist
+        .unwrap_or(self.should_scope_hoist),
+      should_optimize: overrides.should_optimize.unwrap_or(self.should_optimize),
+      source_map: overrides.source_map.or_else(|| self.source_map.clone()),
+      source_type: overrides.source_type.unwrap_or(self.source_type),
+    }
+  }
+
+  /// Hashes the environment consistently across processes and runs
+  ///
+  /// Unlike hashing with the default hasher, which is randomly seeded, this can be used as part of
+  /// persisted request ids. Like equality, the hash does not include `loc`.
+  ///
+  pub fn stable_hash(&self) -> u64 {
+    let mut hasher = Xxh3::default();
+    self.hash(&mut hasher);
+    hasher.finish()
+  }
+}
+
+/// Environment fields that should replace those inherited from a parent environment
+#[derive(Clone, Debug, Default)]
+pub struct EnvironmentOverrides {
+  pub context: Option<EnvironmentContext>,
+  pub engines: Option<Engines>,
+  pub include_node_modules: Option<IncludeNodeModules>,
+  pub is_library: Option<bool>,
+  pub loc: Option<SourceLocation>,
+  pub output_format: Option<OutputFormat>,
+  pub should_scope_hoist: Option<bool>,
+  pub should_optimize: Option<bool>,
+  pub source_map: Option<TargetSourceMapOptions>,
+  pub source_type: Option<SourceType>,
+}
+
+impl Hash for Environment {
+  fn hash<H: Hasher>(&self, state: &mut H) {
     // Hashing intentionally does not include loc
     self.context.hash(state);
     self.engines.hash(state);
@@ -166,3 +221,104 @@ pub struct TargetSourceMapOptions {
   ///
   source_root: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+  use std::num::NonZeroU16;
+  use std::path::PathBuf;
+
+  use super::version::Version;
+  use super::*;
+  use crate::types::source::Location;
+
+  fn environment() -> Environment {
+    Environment {
+      context: EnvironmentContext::Browser,
+      engines: Engines {
+        node: Some(Version::new(NonZeroU16::new(18).unwrap(), 0)),
+        ..Engines::default()
+      },
+      include_node_modules: IncludeNodeModules::default(),
+      is_library: false,
+      loc: None,
+      output_format: OutputFormat::EsModule,
+      should_scope_hoist: true,
+      should_optimize: true,
+      source_map: None,
+      source_type: SourceType::Module,
+    }
+  }
+
+  #[test]
+  fn merge_without_overrides_returns_same_environment() {
+    assert_eq!(
+      environment().merge(EnvironmentOverrides::default()),
+      environment()
+    );
+  }
+
+  #[test]
+  fn merge_overrides_context_and_preserves_engines() {
+    let env = environment().merge(EnvironmentOverrides {
+      context: Some(EnvironmentContext::WebWorker),
+      ..EnvironmentOverrides::default()
+    });
+
+    assert_eq!(
+      env,
+      Environment {
+        context: EnvironmentContext::WebWorker,
+        ..environment()
+      }
+    );
+    assert_eq!(env.engines, environment().engines);
+  }
+
+  #[test]
+  fn stable_hash_is_equal_for_equal_environments() {
+    let env = Environment {
+      loc: Some(SourceLocation {
+        file_path: PathBuf::from("package.json"),
+        start: Location { line: 1, column: 1 },
+        end: Location { line: 1, column: 2 },
+      }),
+      ..environment()
+    };
+
+    assert_eq!(env.stable_hash(), environment().stable_hash());
+  }
+
+  #[test]
+  fn stable_hash_differs_for_distinct_environments() {
+    let node = Environment {
+      context: EnvironmentContext::Node,
+      ..environment()
+    };
+
+    assert_ne!(node.stable_hash(), environment().stable_hash());
+  }
+
+  #[test]
+  fn serializes_environment() {
+    assert_eq!(
+      serde_json::to_value(environment()).unwrap(),
+      serde_json::json!({
+        "context": 0,
+        "engines": {
+          "browsers": "",
+          "electron": null,
+          "node": ">= 18",
+          "parcel": null,
+        },
+        "includeNodeModules": { "Bool": true },
+        "isLibrary": false,
+        "loc": null,
+        "outputFormat": 2,
+        "shouldScopeHoist": true,
+        "shouldOptimize": true,
+        "sourceMap": null,
+        "sourceType": 0,
+      })
+    );
+  }
+}
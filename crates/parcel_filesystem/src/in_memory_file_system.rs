@@ -1,25 +1,119 @@
 use std::collections::HashMap;
-use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
 
+use crate::watch::FileEvent;
+use crate::watch::WatchCallback;
+use crate::watch::WatchHandle;
 use crate::FileSystem;
+use crate::FileType;
 
 /// In memory implementation of a file-system entry
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 enum InMemoryFileSystemEntry {
-  File { contents: String },
+  File { contents: Vec<u8> },
   Directory,
 }
 
+/// The entries of an in memory file system at the time it was snapshotted
+///
+/// See [`InMemoryFileSystem::snapshot`].
+#[derive(Clone, Debug)]
+pub struct FsSnapshot {
+  files: HashMap<PathBuf, InMemoryFileSystemEntry>,
+}
+
+/// A watch callback shared so that it can be invoked after the watchers lock is released, which
+/// lets it watch other paths or drop its handle
+type SharedWatchCallback = Arc<dyn Fn(FileEvent) + Send + Sync>;
+
+/// Subscriptions created through `FileSystem::watch`
+#[derive(Default)]
+struct InMemoryWatchers {
+  next_id: usize,
+  subscriptions: HashMap<usize, (Vec<PathBuf>, SharedWatchCallback)>,
+}
+
+impl std::fmt::Debug for InMemoryWatchers {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("InMemoryWatchers")
+      .field("next_id", &self.next_id)
+      .field("subscriptions", &self.subscriptions.len())
+      .finish()
+  }
+}
+
 /// In memory implementation of the `FileSystem` trait, for testing purpouses.
 #[derive(Debug)]
 pub struct InMemoryFileSystem {
   files: HashMap<PathBuf, InMemoryFileSystemEntry>,
   current_working_directory: PathBuf,
+  watchers: Arc<Mutex<InMemoryWatchers>>,
 }
 
 impl InMemoryFileSystem {
+  /// Creates a file system from an indented description of a directory tree within root
+  ///
+  /// Each line names a directory when it ends with `/`, otherwise a file with optional contents
+  /// following a `:`. Lines indented under a directory are created within it, and root becomes the
+  /// current working directory.
+  ///
+  /// # Examples
+  ///
+  /// ```
+  /// use std::path::Path;
+  ///
+  /// use parcel_filesystem::in_memory_file_system::InMemoryFileSystem;
+  /// use parcel_filesystem::FileSystem;
+  ///
+  /// let fs = InMemoryFileSystem::from_tree(
+  ///   Path::new("/project"),
+  ///   r#"
+  ///     .parcelrc: { "extends": "@parcel/config-default" }
+  ///     src/
+  ///       index.js: console.log('Hello world');
+  ///   "#,
+  /// );
+  ///
+  /// assert!(fs.is_file("/project/src/index.js"));
+  /// ```
+  pub fn from_tree(root: &Path, tree: &str) -> Self {
+    let mut fs = InMemoryFileSystem::default();
+    fs.set_current_working_directory(root.into());
+    fs.create_directory(root);
+
+    // The indentation and path of each directory that contains the current line
+    let mut parents: Vec<(usize, PathBuf)> = Vec::new();
+    for line in tree.lines() {
+      let entry = line.trim_start();
+      if entry.is_empty() {
+        continue;
+      }
+
+      let indent = line.len() - entry.len();
+      while parents.last().is_some_and(|(parent, _)| *parent >= indent) {
+        parents.pop();
+      }
+
+      let dir = parents.last().map_or(root, |(_, dir)| dir.as_path());
+      if let Some(name) = entry.strip_suffix('/') {
+        let path = dir.join(name);
+        fs.create_directory(&path);
+        parents.push((indent, path));
+      } else {
+        let (name, contents) = entry.split_once(':').unwrap_or((entry, ""));
+        fs.write_file(
+          dir.join(name),
+          contents.strip_prefix(' ').unwrap_or(contents),
+        );
+      }
+    }
+
+    fs
+  }
+
   /// Change the current working directory. Used for resolving relative paths.
   pub fn set_current_working_directory(&mut self, cwd: PathBuf) {
     self.current_working_directory = cwd;
@@ -32,12 +126,49 @@ impl InMemoryFileSystem {
       .insert(path.as_ref().into(), InMemoryFileSystemEntry::Directory);
   }
 
-  /// Write a file at path.
-  pub fn write_file(&mut self, path: impl AsRef<Path>, contents: String) {
+  /// Write a file at path, notifying any watchers of the change.
+  pub fn write_file(&mut self, path: impl AsRef<Path>, contents: impl Into<Vec<u8>>) {
+    let path = path.as_ref();
+    let event = if self.is_file(path) {
+      FileEvent::Update(path.into())
+    } else {
+      FileEvent::Create(path.into())
+    };
+
     self.files.insert(
-      path.as_ref().into(),
-      InMemoryFileSystemEntry::File { contents },
+      path.into(),
+      InMemoryFileSystemEntry::File {
+        contents: contents.into(),
+      },
     );
+
+    let callbacks: Vec<_> = self
+      .watchers
+      .lock()
+      .unwrap()
+      .subscriptions
+      .values()
+      .filter(|(paths, _callback)| paths.iter().any(|watched| path.starts_with(watched)))
+      .map(|(_paths, callback)| Arc::clone(callback))
+      .collect();
+
+    for callback in callbacks {
+      callback(event.clone());
+    }
+  }
+
+  /// Captures the current files and directories, so they can be restored later
+  pub fn snapshot(&self) -> FsSnapshot {
+    FsSnapshot {
+      files: self.files.clone(),
+    }
+  }
+
+  /// Replaces all files and directories with those captured in a snapshot
+  ///
+  /// Watchers and the current working directory are kept, and no events are emitted.
+  pub fn restore(&mut self, snapshot: FsSnapshot) {
+    self.files = snapshot.files;
   }
 }
 
@@ -46,6 +177,7 @@ impl Default for InMemoryFileSystem {
     Self {
       files: Default::default(),
       current_working_directory: PathBuf::from("/"),
+      watchers: Default::default(),
     }
   }
 }
@@ -56,37 +188,10 @@ impl FileSystem for InMemoryFileSystem {
   }
 
   fn canonicalize_base<P: AsRef<Path>>(&self, path: P) -> std::io::Result<PathBuf> {
-    let path = path.as_ref();
-
-    let mut result = if path.is_absolute() {
-      vec![]
-    } else {
-      self.current_working_directory.components().collect()
-    };
-
-    let components = path.components();
-    for component in components {
-      match component {
-        Component::Prefix(prefix) => {
-          result = vec![Component::Prefix(prefix)];
-        }
-        Component::RootDir => {
-          result = vec![Component::RootDir];
-        }
-        Component::CurDir => {}
-        Component::ParentDir => {
-          result.pop();
-        }
-        Component::Normal(path) => {
-          result.push(Component::Normal(path));
-        }
-      }
-    }
-
-    Ok(PathBuf::from_iter(result))
+    Ok(self.resolve(&self.current_working_directory, path.as_ref()))
   }
 
-  fn read_to_string<P: AsRef<Path>>(&self, path: P) -> std::io::Result<String> {
+  fn read<P: AsRef<Path>>(&self, path: P) -> std::io::Result<Vec<u8>> {
     self.files.get(path.as_ref()).map_or_else(
       || {
         Err(std::io::Error::new(
@@ -104,6 +209,11 @@ impl FileSystem for InMemoryFileSystem {
     )
   }
 
+  fn read_to_string<P: AsRef<Path>>(&self, path: P) -> std::io::Result<String> {
+    String::from_utf8(self.read(path)?)
+      .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+  }
+
   fn is_file<P: AsRef<Path>>(&self, path: P) -> bool {
     let file = self.files.get(path.as_ref());
     matches!(file, Some(InMemoryFileSystemEntry::File { .. }))
@@ -113,6 +223,60 @@ impl FileSystem for InMemoryFileSystem {
     let file = self.files.get(path.as_ref());
     matches!(file, Some(InMemoryFileSystemEntry::Directory { .. }))
   }
+
+  fn file_type<P: AsRef<Path>>(&self, path: P) -> Option<FileType> {
+    self.files.get(path.as_ref()).map(|entry| match entry {
+      InMemoryFileSystemEntry::File { .. } => FileType::File,
+      InMemoryFileSystemEntry::Directory => FileType::Dir,
+    })
+  }
+
+  /// Lists the entries within path, including the parents of files that were written without
+  /// creating their directories
+  fn read_dir<P: AsRef<Path>>(&self, path: P) -> std::io::Result<Vec<PathBuf>> {
+    let path = path.as_ref();
+    if self.is_file(path) {
+      return Err(std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        "Path is a file",
+      ));
+    }
+
+    let mut entries: Vec<PathBuf> = self
+      .files
+      .keys()
+      .filter_map(|file| {
+        let child = file.strip_prefix(path).ok()?.components().next()?;
+        Some(path.join(child))
+      })
+      .collect();
+
+    if entries.is_empty() && !self.is_dir(path) {
+      return Err(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        "Directory not found",
+      ));
+    }
+
+    entries.sort();
+    entries.dedup();
+
+    Ok(entries)
+  }
+
+  fn watch(&self, paths: &[PathBuf], callback: WatchCallback) -> std::io::Result<WatchHandle> {
+    let mut watchers = self.watchers.lock().unwrap();
+    let id = watchers.next_id;
+    watchers.next_id += 1;
+    watchers
+      .subscriptions
+      .insert(id, (paths.to_vec(), Arc::from(callback)));
+
+    let watchers = Arc::clone(&self.watchers);
+    Ok(WatchHandle::new(move || {
+      watchers.lock().unwrap().subscriptions.remove(&id);
+    }))
+  }
 }
 
 #[cfg(test)]
@@ -155,6 +319,36 @@ mod test {
     assert_eq!(result, PathBuf::from("/other/foo/baz"));
   }
 
+  #[test]
+  fn test_resolve_current_dir() {
+    let fs = InMemoryFileSystem::default();
+    let result = fs.resolve(Path::new("/foo/bar"), Path::new("./baz/./qux"));
+    assert_eq!(result, PathBuf::from("/foo/bar/baz/qux"));
+  }
+
+  #[test]
+  fn test_resolve_parent_dir() {
+    let fs = InMemoryFileSystem::default();
+    let result = fs.resolve(Path::new("/foo/bar"), Path::new("../base.parcelrc"));
+    assert_eq!(result, PathBuf::from("/foo/base.parcelrc"));
+  }
+
+  #[test]
+  fn test_resolve_nested_parent_dirs() {
+    let fs = InMemoryFileSystem::default();
+    let result = fs.resolve(
+      Path::new("/foo/bar/baz"),
+      Path::new("../qux/../../base.parcelrc"),
+    );
+    assert_eq!(result, PathBuf::from("/foo/base.parcelrc"));
+
+    let result = fs.resolve(Path::new("/foo"), Path::new("../../base.parcelrc"));
+    assert_eq!(result, PathBuf::from("/base.parcelrc"));
+
+    let result = fs.resolve(Path::new("foo"), Path::new("../../base.parcelrc"));
+    assert_eq!(result, PathBuf::from("../base.parcelrc"));
+  }
+
   #[test]
   fn test_read_file() {
     let mut fs = InMemoryFileSystem::default();
@@ -170,6 +364,208 @@ mod test {
     assert!(result.is_err());
   }
 
+  #[test]
+  fn test_read_binary_file() {
+    let mut fs = InMemoryFileSystem::default();
+    fs.write_file(
+      PathBuf::from("/foo/bar.png"),
+      vec![0x89, 0x50, 0x4e, 0x47, 0xff],
+    );
+    let result = fs.read(Path::new("/foo/bar.png")).unwrap();
+    assert_eq!(result, vec![0x89, 0x50, 0x4e, 0x47, 0xff]);
+  }
+
+  #[test]
+  fn test_read_binary_file_to_string() {
+    let mut fs = InMemoryFileSystem::default();
+    fs.write_file(
+      PathBuf::from("/foo/bar.png"),
+      vec![0x89, 0x50, 0x4e, 0x47, 0xff],
+    );
+    let result = fs.read_to_string(Path::new("/foo/bar.png"));
+    assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+  }
+
+  #[test]
+  fn test_read_file_with_token() {
+    let mut fs = InMemoryFileSystem::default();
+    fs.write_file("/foo/bar", "contents");
+    fs.write_file("/foo/baz", "contents");
+
+    let (contents, token) = fs.read_file_with_token("/foo/bar").unwrap();
+    assert_eq!(contents, "contents");
+    assert!(fs.is_token_valid("/foo/bar", &token));
+
+    // Rewriting the same contents keeps the token valid
+    fs.write_file("/foo/bar", "contents");
+    assert!(fs.is_token_valid("/foo/bar", &token));
+
+    fs.write_file("/foo/bar", "updated");
+    assert!(!fs.is_token_valid("/foo/bar", &token));
+    assert!(!fs.is_token_valid("/foo/missing", &token));
+  }
+
+  #[test]
+  fn test_watch() {
+    let mut fs = InMemoryFileSystem::default();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let handle = fs
+      .watch(&[PathBuf::from("/foo")], {
+        let events = Arc::clone(&events);
+        Box::new(move |event| events.lock().unwrap().push(event))
+      })
+      .unwrap();
+
+    fs.write_file(PathBuf::from("/foo/bar"), "contents".to_string());
+    fs.write_file(PathBuf::from("/foo/bar"), "updated".to_string());
+    fs.write_file(PathBuf::from("/baz"), "contents".to_string());
+    drop(handle);
+    fs.write_file(PathBuf::from("/foo/qux"), "contents".to_string());
+
+    assert_eq!(
+      *events.lock().unwrap(),
+      vec![
+        FileEvent::Create(PathBuf::from("/foo/bar")),
+        FileEvent::Update(PathBuf::from("/foo/bar")),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_watch_callback_can_unsubscribe() {
+    let mut fs = InMemoryFileSystem::default();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let handle = fs
+      .watch(&[PathBuf::from("/foo")], {
+        let events = Arc::clone(&events);
+        let watchers = Arc::clone(&fs.watchers);
+        Box::new(move |event| {
+          events.lock().unwrap().push(event);
+          watchers.lock().unwrap().subscriptions.clear();
+        })
+      })
+      .unwrap();
+
+    fs.write_file(PathBuf::from("/foo/bar"), "contents".to_string());
+    fs.write_file(PathBuf::from("/foo/bar"), "updated".to_string());
+    drop(handle);
+
+    assert_eq!(
+      *events.lock().unwrap(),
+      vec![FileEvent::Create(PathBuf::from("/foo/bar"))]
+    );
+  }
+
+  #[test]
+  fn test_from_tree() {
+    let fs = InMemoryFileSystem::from_tree(
+      Path::new("/project"),
+      r#"
+        package.json: {"name": "project"}
+        src/
+          index.js: import './a/b.js';
+          a/
+            b.js: export default 1;
+          empty.txt
+        styles/
+          index.css: body {}
+      "#,
+    );
+
+    assert_eq!(fs.cwd().unwrap(), PathBuf::from("/project"));
+    assert_eq!(
+      fs.read_to_string("/project/package.json").unwrap(),
+      r#"{"name": "project"}"#
+    );
+    assert_eq!(
+      fs.read_to_string("/project/src/index.js").unwrap(),
+      "import './a/b.js';"
+    );
+    assert_eq!(
+      fs.read_to_string("/project/src/a/b.js").unwrap(),
+      "export default 1;"
+    );
+    assert_eq!(fs.read_to_string("/project/src/empty.txt").unwrap(), "");
+    assert_eq!(
+      fs.read_to_string("/project/styles/index.css").unwrap(),
+      "body {}"
+    );
+    assert!(fs.is_dir("/project/src/a"));
+    assert_eq!(
+      fs.read_dir("/project").unwrap(),
+      vec![
+        PathBuf::from("/project/package.json"),
+        PathBuf::from("/project/src"),
+        PathBuf::from("/project/styles"),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_read_dir() {
+    let mut fs = InMemoryFileSystem::default();
+    fs.create_directory("/foo/empty");
+    fs.write_file("/foo/b.txt", "b");
+    fs.write_file("/foo/a.txt", "a");
+    fs.write_file("/foo/bar/c.txt", "c");
+
+    assert_eq!(
+      fs.read_dir("/foo").unwrap(),
+      vec![
+        PathBuf::from("/foo/a.txt"),
+        PathBuf::from("/foo/b.txt"),
+        PathBuf::from("/foo/bar"),
+        PathBuf::from("/foo/empty"),
+      ]
+    );
+    assert_eq!(fs.read_dir("/foo/empty").unwrap(), Vec::<PathBuf>::new());
+    assert_eq!(
+      fs.read_dir("/foo/missing").unwrap_err().kind(),
+      std::io::ErrorKind::NotFound
+    );
+    assert_eq!(
+      fs.read_dir("/foo/a.txt").unwrap_err().kind(),
+      std::io::ErrorKind::InvalidInput
+    );
+  }
+
+  #[test]
+  fn test_file_type() {
+    let mut fs = InMemoryFileSystem::default();
+    fs.create_directory("/foo");
+    fs.write_file("/foo/bar.txt", "bar");
+
+    assert_eq!(fs.file_type("/foo/bar.txt"), Some(FileType::File));
+    assert_eq!(fs.file_type("/foo"), Some(FileType::Dir));
+    assert_eq!(fs.file_type("/foo/missing.txt"), None);
+    assert!(fs.exists("/foo/bar.txt"));
+    assert!(fs.exists("/foo"));
+    assert!(!fs.exists("/foo/missing.txt"));
+  }
+
+  #[test]
+  fn test_snapshot_and_restore() {
+    let mut fs = InMemoryFileSystem::default();
+    fs.write_file("/foo/a.txt", "a");
+    fs.create_directory("/foo/empty");
+
+    let snapshot = fs.snapshot();
+
+    fs.write_file("/foo/a.txt", "updated");
+    fs.write_file("/foo/b.txt", "b");
+
+    fs.restore(snapshot.clone());
+
+    assert_eq!(fs.read_to_string("/foo/a.txt").unwrap(), "a");
+    assert!(!fs.is_file("/foo/b.txt"));
+    assert!(fs.is_dir("/foo/empty"));
+
+    // Snapshots can be restored more than once
+    fs.write_file("/foo/c.txt", "c");
+    fs.restore(snapshot);
+    assert!(!fs.is_file("/foo/c.txt"));
+  }
+
   #[test]
   fn test_is_file() {
     let mut fs = InMemoryFileSystem::default();
@@ -31,6 +31,18 @@ fn path_from_js(result: JsUnknown) -> napi::Result<PathBuf> {
   Ok(PathBuf::from(result_string))
 }
 
+// JS strings cannot hold paths that are not valid UTF-8, so these are reported as errors
+fn path_to_js(path: &Path) -> anyhow::Result<&str> {
+  path
+    .to_str()
+    .ok_or_else(|| anyhow::anyhow!("Path is not valid UTF-8: {}", path.display()))
+}
+
+// readdirSync returns the names of the entries rather than their paths
+fn entry_paths(dir: &Path, names: impl IntoIterator<Item = PathBuf>) -> Vec<PathBuf> {
+  names.into_iter().map(|name| dir.join(name)).collect()
+}
+
 // Convert arbitrary errors to io errors. This is wrong; the `FileSystem` trait should use
 // `anyhow::Result`
 fn run_with_errors<T>(block: impl FnOnce() -> anyhow::Result<T>) -> Result<T, std::io::Error> {
@@ -48,7 +60,7 @@ impl FileSystem for JSDelegateFileSystem {
 
   fn canonicalize_base<P: AsRef<Path>>(&self, path: P) -> std::io::Result<PathBuf> {
     run_with_errors(|| {
-      let path = path.as_ref().to_str().unwrap();
+      let path = path_to_js(path.as_ref())?;
       let js_path = self.env.create_string(path)?;
       let result = call_method(
         &self.env,
@@ -60,9 +72,9 @@ impl FileSystem for JSDelegateFileSystem {
     })
   }
 
-  fn read_to_string<P: AsRef<Path>>(&self, path: P) -> std::io::Result<String> {
+  fn read<P: AsRef<Path>>(&self, path: P) -> std::io::Result<Vec<u8>> {
     run_with_errors(|| {
-      let path = path.as_ref().to_str().unwrap();
+      let path = path_to_js(path.as_ref())?;
       let js_path = self.env.create_string(path)?;
       let result = call_method(
         &self.env,
@@ -74,14 +86,55 @@ impl FileSystem for JSDelegateFileSystem {
       let buffer = napi::JsBuffer::from_unknown(result)?;
       let buffer = buffer.into_value()?;
       let buffer: &[u8] = buffer.as_ref();
-      let result = String::from_utf8(buffer.to_vec())?;
-      Ok(result)
+      Ok(buffer.to_vec())
+    })
+  }
+
+  fn read_to_string<P: AsRef<Path>>(&self, path: P) -> std::io::Result<String> {
+    let bytes = self.read(path)?;
+    run_with_errors(|| Ok(String::from_utf8(bytes)?))
+  }
+
+  fn write<P: AsRef<Path>>(&self, path: P, contents: &[u8]) -> std::io::Result<()> {
+    run_with_errors(|| {
+      let path = path_to_js(path.as_ref())?;
+      let js_path = self.env.create_string(path)?;
+      let js_contents = self.env.create_buffer_copy(contents)?.into_raw();
+      call_method(
+        &self.env,
+        &self.js_delegate,
+        "writeFileSync",
+        &[&js_path.into_unknown(), &js_contents.into_unknown()],
+      )?;
+      Ok(())
+    })
+  }
+
+  fn read_dir<P: AsRef<Path>>(&self, path: P) -> std::io::Result<Vec<PathBuf>> {
+    run_with_errors(|| {
+      let dir = path.as_ref();
+      let js_path = self.env.create_string(path_to_js(dir)?)?;
+      let result = call_method(
+        &self.env,
+        &self.js_delegate,
+        "readdirSync",
+        &[&js_path.into_unknown()],
+      )?;
+
+      let js_names = result.coerce_to_object()?;
+      let mut names = Vec::new();
+      for index in 0..js_names.get_array_length()? {
+        let name: JsUnknown = js_names.get_element(index)?;
+        names.push(path_from_js(name)?);
+      }
+
+      Ok(entry_paths(dir, names))
     })
   }
 
   fn is_file<P: AsRef<Path>>(&self, path: P) -> bool {
     run_with_errors(|| {
-      let path = path.as_ref().to_str().unwrap();
+      let path = path_to_js(path.as_ref())?;
       let js_path = self.env.create_string(path)?;
       let result = call_method(
         &self.env,
@@ -98,7 +151,7 @@ impl FileSystem for JSDelegateFileSystem {
 
   fn is_dir<P: AsRef<Path>>(&self, path: P) -> bool {
     run_with_errors(|| {
-      let path = path.as_ref().to_str().unwrap();
+      let path = path_to_js(path.as_ref())?;
       let js_path = self.env.create_string(path)?;
       let result = call_method(
         &self.env,
@@ -113,3 +166,55 @@ impl FileSystem for JSDelegateFileSystem {
     .unwrap_or(false)
   }
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_path_to_js() {
+    assert_eq!(path_to_js(Path::new("/foo/bar.js")).unwrap(), "/foo/bar.js");
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_path_to_js_errors_for_paths_that_are_not_utf8() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = Path::new(OsStr::from_bytes(b"/foo/\xff.js"));
+    let error = run_with_errors(|| Ok(path_to_js(path)?.to_string())).unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::Other);
+    assert_eq!(
+      error.to_string(),
+      format!("Path is not valid UTF-8: {}", path.display())
+    );
+  }
+
+  #[test]
+  fn test_entry_paths_joins_names_to_the_directory() {
+    assert_eq!(
+      entry_paths(
+        Path::new("/foo"),
+        vec![PathBuf::from("a.js"), PathBuf::from("bar")]
+      ),
+      vec![PathBuf::from("/foo/a.js"), PathBuf::from("/foo/bar")]
+    );
+    assert_eq!(
+      entry_paths(Path::new("/foo"), Vec::new()),
+      Vec::<PathBuf>::new()
+    );
+  }
+
+  #[test]
+  fn test_run_with_errors_maps_errors_to_io_errors() {
+    let error =
+      run_with_errors(|| -> anyhow::Result<()> { Err(anyhow::anyhow!("readdirSync failed")) })
+        .unwrap_err();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::Other);
+    assert_eq!(error.to_string(), "readdirSync failed");
+    assert_eq!(run_with_errors(|| Ok(1)).unwrap(), 1);
+  }
+}
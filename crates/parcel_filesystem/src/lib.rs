@@ -1,8 +1,14 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
 use std::io::Result;
+use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
 
 use dashmap::DashMap;
+use watch::WatchCallback;
+use watch::WatchHandle;
 
 /// FileSystem implementation that delegates calls to a JS object
 pub mod js_delegate_file_system;
@@ -12,10 +18,34 @@ pub mod in_memory_file_system;
 
 pub mod search;
 
+/// Types for subscribing to file-system changes
+pub mod watch;
+
 /// File-system implementation using std::fs and a canonicalize cache
 #[cfg(not(target_arch = "wasm32"))]
 pub mod os_file_system;
 
+/// The kinds of entries a path can refer to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileType {
+  Dir,
+  File,
+}
+
+/// Identifies the contents of a file at the time it was read, to check whether it has since changed
+///
+/// Tokens hash the file contents, so they are only comparable within the same process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FileToken(u64);
+
+impl FileToken {
+  pub fn from_contents(contents: &[u8]) -> Self {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    FileToken(hasher.finish())
+  }
+}
+
 /// Trait abstracting file-system operations
 /// .
 ///
@@ -45,7 +75,88 @@ pub trait FileSystem {
   ) -> Result<PathBuf> {
     self.canonicalize_base(path)
   }
+  /// Reads the raw bytes of a file, for contents that may not be valid UTF-8
+  fn read<P: AsRef<Path>>(&self, path: P) -> Result<Vec<u8>> {
+    self.read_to_string(path).map(String::into_bytes)
+  }
   fn read_to_string<P: AsRef<Path>>(&self, path: P) -> Result<String>;
+  /// Writes contents to a file, replacing it if it exists
+  fn write<P: AsRef<Path>>(&self, _path: P, _contents: &[u8]) -> Result<()> {
+    Err(std::io::Error::new(
+      std::io::ErrorKind::Unsupported,
+      "Writing files is not supported",
+    ))
+  }
+  /// Creates a directory along with any of its missing parents
+  fn create_dir_all<P: AsRef<Path>>(&self, _path: P) -> Result<()> {
+    Err(std::io::Error::new(
+      std::io::ErrorKind::Unsupported,
+      "Creating directories is not supported",
+    ))
+  }
+  /// Reads a file along with a token that can later be checked with `is_token_valid`
+  fn read_file_with_token<P: AsRef<Path>>(&self, path: P) -> Result<(String, FileToken)> {
+    let contents = self.read_to_string(path)?;
+    let token = FileToken::from_contents(contents.as_bytes());
+    Ok((contents, token))
+  }
+  /// Returns whether the file still has the contents it had when the token was created
+  fn is_token_valid<P: AsRef<Path>>(&self, path: P, token: &FileToken) -> bool {
+    self
+      .read(path)
+      .is_ok_and(|contents| FileToken::from_contents(&contents) == *token)
+  }
+  /// Joins a relative path onto base, normalizing `.` and `..` segments without touching the disk
+  fn resolve(&self, base: &Path, relative: &Path) -> PathBuf {
+    let mut result: Vec<Component> = Vec::new();
+    let path = base.join(relative);
+
+    for component in path.components() {
+      match component {
+        Component::CurDir => {}
+        Component::ParentDir => match result.last() {
+          Some(Component::Normal(_)) => {
+            result.pop();
+          }
+          // The parent of the root is the root itself
+          Some(Component::Prefix(_) | Component::RootDir) => {}
+          _ => result.push(component),
+        },
+        Component::Prefix(_) | Component::RootDir | Component::Normal(_) => result.push(component),
+      }
+    }
+
+    PathBuf::from_iter(result)
+  }
   fn is_file<P: AsRef<Path>>(&self, path: P) -> bool;
   fn is_dir<P: AsRef<Path>>(&self, path: P) -> bool;
+  /// Returns whether path is a file or directory, or None when it does not exist
+  ///
+  /// Implementations should override this when they can answer with a single lookup.
+  fn file_type<P: AsRef<Path>>(&self, path: P) -> Option<FileType> {
+    if self.is_file(&path) {
+      Some(FileType::File)
+    } else if self.is_dir(&path) {
+      Some(FileType::Dir)
+    } else {
+      None
+    }
+  }
+  fn exists<P: AsRef<Path>>(&self, path: P) -> bool {
+    self.file_type(path).is_some()
+  }
+  /// Lists the paths of the entries directly within a directory
+  fn read_dir<P: AsRef<Path>>(&self, _path: P) -> Result<Vec<PathBuf>> {
+    Err(std::io::Error::new(
+      std::io::ErrorKind::Unsupported,
+      "Reading directories is not supported",
+    ))
+  }
+  /// Calls the callback whenever a file within the given paths is created, updated, or deleted
+  fn watch(&self, _paths: &[PathBuf], _callback: WatchCallback) -> Result<WatchHandle> {
+    Err(std::io::Error::new(
+      std::io::ErrorKind::Unsupported,
+      "Watching is not supported",
+    ))
+  }
 }
@@ -3,31 +3,82 @@ use std::path::PathBuf;
 
 use canonicalize::canonicalize;
 use dashmap::DashMap;
+use notify::EventKind;
+use notify::RecursiveMode;
+use notify::Watcher;
 
+use crate::watch::FileEvent;
+use crate::watch::WatchCallback;
+use crate::watch::WatchHandle;
 use crate::FileSystem;
+use crate::FileType;
 
 mod canonicalize;
 
-#[derive(Default)]
-pub struct OsFileSystem;
+pub struct OsFileSystem {
+  follow_symlinks: bool,
+}
+
+impl Default for OsFileSystem {
+  fn default() -> Self {
+    Self {
+      follow_symlinks: true,
+    }
+  }
+}
+
+impl OsFileSystem {
+  /// Sets whether canonicalizing resolves symlinks, or only normalizes `.` and `..` segments so
+  /// that symlinked packages keep their symlink path
+  ///
+  /// Symlinks are followed by default.
+  pub fn with_follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+    self.follow_symlinks = follow_symlinks;
+    self
+  }
+}
 
 impl FileSystem for OsFileSystem {
   fn cwd(&self) -> std::io::Result<PathBuf> {
     std::env::current_dir()
   }
 
+  fn canonicalize_base<P: AsRef<Path>>(&self, path: P) -> std::io::Result<PathBuf> {
+    if self.follow_symlinks {
+      std::fs::canonicalize(path)
+    } else {
+      Ok(self.resolve(&self.cwd()?, path.as_ref()))
+    }
+  }
+
   fn canonicalize<P: AsRef<Path>>(
     &self,
     path: P,
     cache: &DashMap<PathBuf, Option<PathBuf>>,
   ) -> std::io::Result<PathBuf> {
-    canonicalize(path.as_ref(), cache)
+    if self.follow_symlinks {
+      canonicalize(path.as_ref(), cache)
+    } else {
+      self.canonicalize_base(path)
+    }
+  }
+
+  fn read<P: AsRef<Path>>(&self, path: P) -> std::io::Result<Vec<u8>> {
+    std::fs::read(path)
   }
 
   fn read_to_string<P: AsRef<Path>>(&self, path: P) -> std::io::Result<String> {
     std::fs::read_to_string(path)
   }
 
+  fn write<P: AsRef<Path>>(&self, path: P, contents: &[u8]) -> std::io::Result<()> {
+    std::fs::write(path, contents)
+  }
+
+  fn create_dir_all<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+    std::fs::create_dir_all(path)
+  }
+
   fn is_file<P: AsRef<Path>>(&self, path: P) -> bool {
     let path: &Path = path.as_ref();
     path.is_file()
@@ -37,4 +88,136 @@ impl FileSystem for OsFileSystem {
     let path: &Path = path.as_ref();
     path.is_dir()
   }
+
+  fn file_type<P: AsRef<Path>>(&self, path: P) -> Option<FileType> {
+    let file_type = std::fs::metadata(path).ok()?.file_type();
+    if file_type.is_file() {
+      Some(FileType::File)
+    } else if file_type.is_dir() {
+      Some(FileType::Dir)
+    } else {
+      None
+    }
+  }
+
+  fn read_dir<P: AsRef<Path>>(&self, path: P) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::read_dir(path)?
+      .map(|entry| entry.map(|entry| entry.path()))
+      .collect()
+  }
+
+  fn watch(&self, paths: &[PathBuf], callback: WatchCallback) -> std::io::Result<WatchHandle> {
+    let to_io_error = |err: notify::Error| std::io::Error::new(std::io::ErrorKind::Other, err);
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+      let Ok(event) = event else {
+        return;
+      };
+
+      let file_event = match event.kind {
+        EventKind::Create(_) => FileEvent::Create,
+        EventKind::Modify(_) => FileEvent::Update,
+        EventKind::Remove(_) => FileEvent::Delete,
+        _ => return,
+      };
+
+      for path in event.paths {
+        callback(file_event(path));
+      }
+    })
+    .map_err(to_io_error)?;
+
+    for path in paths {
+      watcher
+        .watch(path, RecursiveMode::Recursive)
+        .map_err(to_io_error)?;
+    }
+
+    // The watcher stops when it is dropped along with the handle
+    Ok(WatchHandle::new(move || drop(watcher)))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use assert_fs::prelude::*;
+
+  use super::*;
+
+  #[test]
+  fn test_file_type() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    dir.child("foo/bar.js").write_str("")?;
+
+    let fs = OsFileSystem::default();
+
+    assert_eq!(fs.file_type(dir.child("foo/bar.js")), Some(FileType::File));
+    assert_eq!(fs.file_type(dir.child("foo")), Some(FileType::Dir));
+    assert_eq!(fs.file_type(dir.child("missing.js")), None);
+    assert!(fs.exists(dir.child("foo")));
+    assert!(!fs.exists(dir.child("missing.js")));
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_write_and_read_dir() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let fs = OsFileSystem::default();
+
+    fs.write(dir.child("a.txt"), b"a")?;
+    fs.write(dir.child("b.txt"), b"b")?;
+
+    let mut entries = fs.read_dir(dir.path())?;
+    entries.sort();
+
+    assert_eq!(
+      entries,
+      vec![dir.child("a.txt").path(), dir.child("b.txt").path()]
+    );
+    assert_eq!(fs.read(dir.child("b.txt"))?, b"b");
+
+    Ok(())
+  }
+
+  #[test]
+  fn test_follow_symlinks() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(windows)]
+    if !is_elevated::is_elevated() {
+      println!("skipping symlink tests due to missing permissions");
+      return Ok(());
+    }
+
+    let dir = assert_fs::TempDir::new()?;
+    let root = std::fs::canonicalize(dir.path())?;
+    dir.child("packages/a/index.js").write_str("")?;
+    dir.child("node_modules").create_dir_all()?;
+    dir
+      .child("node_modules/a")
+      .symlink_to_dir(root.join("packages/a"))?;
+
+    let path = root.join("node_modules/a/../a/./index.js");
+    let cache = DashMap::new();
+
+    let fs = OsFileSystem::default();
+    assert_eq!(
+      fs.canonicalize(&path, &cache)?,
+      root.join("packages/a/index.js")
+    );
+    assert_eq!(
+      fs.canonicalize_base(&path)?,
+      root.join("packages/a/index.js")
+    );
+
+    let fs = OsFileSystem::default().with_follow_symlinks(false);
+    assert_eq!(
+      fs.canonicalize(&path, &cache)?,
+      root.join("node_modules/a/index.js")
+    );
+    assert_eq!(
+      fs.canonicalize_base(&path)?,
+      root.join("node_modules/a/index.js")
+    );
+
+    Ok(())
+  }
 }
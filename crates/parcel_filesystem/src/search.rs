@@ -3,11 +3,14 @@ use std::path::PathBuf;
 
 use crate::FileSystem;
 
-pub fn find_ancestor_file<P: AsRef<Path>>(
-  fs: &impl FileSystem,
-  filenames: Vec<String>,
+/// Finds the nearest ancestor directory of from, up to root, that the predicate accepts
+///
+/// The search stops at node_modules directories, so packages do not pick up the config of the
+/// project they are installed in.
+pub fn find_ancestor<P: AsRef<Path>>(
   from: P,
   root: P,
+  mut predicate: impl FnMut(&Path) -> bool,
 ) -> Option<PathBuf> {
   for dir in from.as_ref().ancestors() {
     // Break if we hit a node_modules directory
@@ -17,11 +20,8 @@ pub fn find_ancestor_file<P: AsRef<Path>>(
       }
     }
 
-    for name in &filenames {
-      let fullpath = dir.join(name);
-      if fs.is_file(&fullpath) {
-        return Some(fullpath);
-      }
+    if predicate(dir) {
+      return Some(dir.to_path_buf());
     }
 
     if dir == root.as_ref() {
@@ -31,3 +31,84 @@ pub fn find_ancestor_file<P: AsRef<Path>>(
 
   None
 }
+
+pub fn find_ancestor_file<P: AsRef<Path>>(
+  fs: &impl FileSystem,
+  filenames: Vec<String>,
+  from: P,
+  root: P,
+) -> Option<PathBuf> {
+  let mut file = None;
+  find_ancestor(from, root, |dir| {
+    file = filenames
+      .iter()
+      .map(|name| dir.join(name))
+      .find(|fullpath| fs.is_file(fullpath));
+
+    file.is_some()
+  })?;
+
+  file
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::in_memory_file_system::InMemoryFileSystem;
+
+  #[test]
+  fn test_find_ancestor_file() {
+    let mut fs = InMemoryFileSystem::default();
+    fs.write_file("/project/.parcelrc", "{}");
+    fs.write_file("/project/packages/a/package.json", "{}");
+
+    let filenames = vec![String::from(".parcelrc"), String::from("package.json")];
+
+    assert_eq!(
+      find_ancestor_file(&fs, filenames.clone(), "/project/packages/a/src", "/"),
+      Some(PathBuf::from("/project/packages/a/package.json"))
+    );
+    assert_eq!(
+      find_ancestor_file(&fs, filenames.clone(), "/project/packages/b/src", "/"),
+      Some(PathBuf::from("/project/.parcelrc"))
+    );
+    assert_eq!(
+      find_ancestor_file(
+        &fs,
+        filenames.clone(),
+        "/project/packages",
+        "/project/packages"
+      ),
+      None
+    );
+    assert_eq!(
+      find_ancestor_file(&fs, filenames, "/project/node_modules/a", "/"),
+      None
+    );
+  }
+
+  #[test]
+  fn test_find_ancestor_with_predicate() {
+    let mut fs = InMemoryFileSystem::default();
+    fs.write_file("/project/package.json", r#"{ "parcel": {} }"#);
+    fs.write_file("/project/packages/a/package.json", r#"{ "name": "a" }"#);
+
+    let has_parcel_key = |dir: &Path| {
+      fs.read_to_string(dir.join("package.json"))
+        .is_ok_and(|contents| contents.contains(r#""parcel""#))
+    };
+
+    assert_eq!(
+      find_ancestor("/project/packages/a/src", "/", has_parcel_key),
+      Some(PathBuf::from("/project"))
+    );
+    assert_eq!(
+      find_ancestor(
+        "/project/packages/a/src",
+        "/project/packages",
+        has_parcel_key
+      ),
+      None
+    );
+  }
+}
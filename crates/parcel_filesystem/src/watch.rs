@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+/// A change to a watched path
+#[derive(Clone, Debug, PartialEq)]
+pub enum FileEvent {
+  Create(PathBuf),
+  Update(PathBuf),
+  Delete(PathBuf),
+}
+
+/// Callback invoked for every change to a watched path
+pub type WatchCallback = Box<dyn Fn(FileEvent) + Send + Sync>;
+
+/// Keeps a watch subscription alive, unsubscribing when dropped
+pub struct WatchHandle {
+  unwatch: Option<Box<dyn FnOnce()>>,
+}
+
+impl WatchHandle {
+  pub fn new(unwatch: impl FnOnce() + 'static) -> Self {
+    Self {
+      unwatch: Some(Box::new(unwatch)),
+    }
+  }
+}
+
+impl Drop for WatchHandle {
+  fn drop(&mut self) {
+    if let Some(unwatch) = self.unwatch.take() {
+      unwatch();
+    }
+  }
+}
+
+impl std::fmt::Debug for WatchHandle {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("WatchHandle").finish_non_exhaustive()
+  }
+}
@@ -1,6 +1,8 @@
 pub mod package_manager;
+pub mod retrying_package_manager;
 
 pub use package_manager::MockPackageManager;
 pub use package_manager::PackageManager;
 pub use package_manager::Resolution;
 pub use package_manager::ResolveError;
+pub use retrying_package_manager::RetryingPackageManager;
@@ -4,12 +4,15 @@ use std::path::PathBuf;
 use mockall::automock;
 use thiserror::Error;
 
+#[derive(Debug)]
 pub struct Resolution {
   pub resolved: PathBuf,
 }
 
 #[derive(Debug, Error)]
 pub enum ResolveError {
+  #[error("Invalid module specifier '{0}'")]
+  InvalidSpecifier(String),
   #[error("Cannot find module '{0}' from {1}")]
   NotFound(String, String),
 }
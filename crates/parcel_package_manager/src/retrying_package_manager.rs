@@ -0,0 +1,147 @@
+use std::path::Path;
+use std::time::Duration;
+
+use crate::PackageManager;
+use crate::Resolution;
+use crate::ResolveError;
+
+/// The longest delay between retries
+pub const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Retries failed resolutions with an inner package manager, for packages that may still be
+/// being written, such as during a concurrent install
+///
+/// The delay before each retry doubles, starting from the given backoff, up to a maximum of
+/// `MAX_BACKOFF`. Only modules that are not found are retried.
+pub struct RetryingPackageManager<P> {
+  backoff: Duration,
+  inner: P,
+  retries: u32,
+}
+
+impl<P: PackageManager> RetryingPackageManager<P> {
+  pub fn new(inner: P, retries: u32, backoff: Duration) -> Self {
+    Self {
+      backoff,
+      inner,
+      retries,
+    }
+  }
+
+  /// Returns the delay before retrying a failed attempt, where the first attempt is 0
+  fn delay(&self, attempt: u32) -> Duration {
+    2u32
+      .checked_pow(attempt)
+      .and_then(|factor| self.backoff.checked_mul(factor))
+      .map_or(MAX_BACKOFF, |delay| delay.min(MAX_BACKOFF))
+  }
+}
+
+impl<P: PackageManager> PackageManager for RetryingPackageManager<P> {
+  fn resolve(&self, specifier: &str, from: &Path) -> Result<Resolution, ResolveError> {
+    let mut attempt = 0;
+    loop {
+      match self.inner.resolve(specifier, from) {
+        Ok(resolution) => return Ok(resolution),
+        Err(ResolveError::NotFound(..)) if attempt < self.retries => {
+          std::thread::sleep(self.delay(attempt));
+          attempt += 1;
+        }
+        Err(err) => return Err(err),
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::PathBuf;
+
+  use super::*;
+  use crate::MockPackageManager;
+
+  fn not_found(specifier: &str, from: &Path) -> Result<Resolution, ResolveError> {
+    Err(ResolveError::NotFound(
+      String::from(specifier),
+      from.display().to_string(),
+    ))
+  }
+
+  #[test]
+  fn resolves_after_transient_failures() {
+    let mut package_manager = MockPackageManager::new();
+    package_manager
+      .expect_resolve()
+      .times(2)
+      .returning(not_found);
+    package_manager.expect_resolve().times(1).returning(|_, _| {
+      Ok(Resolution {
+        resolved: PathBuf::from("/node_modules/@parcel/transformer-js/index.js"),
+      })
+    });
+
+    let resolution = RetryingPackageManager::new(package_manager, 2, Duration::ZERO)
+      .resolve("@parcel/transformer-js", Path::new("/"))
+      .map(|resolution| resolution.resolved);
+
+    assert_eq!(
+      resolution.map_err(|e| e.to_string()),
+      Ok(PathBuf::from(
+        "/node_modules/@parcel/transformer-js/index.js"
+      ))
+    );
+  }
+
+  #[test]
+  fn returns_other_errors_without_retrying() {
+    let mut package_manager = MockPackageManager::new();
+    package_manager
+      .expect_resolve()
+      .times(1)
+      .returning(|specifier, _from| Err(ResolveError::InvalidSpecifier(String::from(specifier))));
+
+    let resolution = RetryingPackageManager::new(package_manager, 2, Duration::ZERO)
+      .resolve("@parcel/", Path::new("/"))
+      .map(|resolution| resolution.resolved);
+
+    assert_eq!(
+      resolution.map_err(|e| e.to_string()),
+      Err(String::from("Invalid module specifier '@parcel/'"))
+    );
+  }
+
+  #[test]
+  fn caps_the_delay_between_retries() {
+    let package_manager =
+      RetryingPackageManager::new(MockPackageManager::new(), 64, Duration::from_secs(1));
+
+    assert_eq!(package_manager.delay(0), Duration::from_secs(1));
+    assert_eq!(package_manager.delay(3), Duration::from_secs(8));
+    assert_eq!(package_manager.delay(6), MAX_BACKOFF);
+    assert_eq!(package_manager.delay(40), MAX_BACKOFF);
+
+    let package_manager = RetryingPackageManager::new(MockPackageManager::new(), 64, Duration::MAX);
+
+    assert_eq!(package_manager.delay(1), MAX_BACKOFF);
+  }
+
+  #[test]
+  fn errors_when_retries_are_exhausted() {
+    let mut package_manager = MockPackageManager::new();
+    package_manager
+      .expect_resolve()
+      .times(3)
+      .returning(not_found);
+
+    let resolution = RetryingPackageManager::new(package_manager, 2, Duration::ZERO)
+      .resolve("@parcel/transformer-js", Path::new("/"))
+      .map(|resolution| resolution.resolved);
+
+    assert_eq!(
+      resolution.map_err(|e| e.to_string()),
+      Err(String::from(
+        "Cannot find module '@parcel/transformer-js' from /"
+      ))
+    );
+  }
+}
@@ -1243,11 +1243,17 @@ mod tests {
   }
 
   fn test_resolver<'a>() -> Resolver<'a, OsFileSystem> {
-    Resolver::parcel(root().into(), CacheCow::Owned(Cache::new(OsFileSystem)))
+    Resolver::parcel(
+      root().into(),
+      CacheCow::Owned(Cache::new(OsFileSystem::default())),
+    )
   }
 
   fn node_resolver<'a>() -> Resolver<'a, OsFileSystem> {
-    Resolver::node(root().into(), CacheCow::Owned(Cache::new(OsFileSystem)))
+    Resolver::node(
+      root().into(),
+      CacheCow::Owned(Cache::new(OsFileSystem::default())),
+    )
   }
 
   #[test]